@@ -0,0 +1,275 @@
+use ruby::*;
+use super::{protect, block_proc, FromValue, ToValue, ConversionError, RubyException, RubyType, Hash, KwArgs, Proc};
+use std::slice;
+
+// Parsed rb_scan_args format: [lead][optional][*][trailing][:][&]
+// e.g. "12*:&" - one required, two optional, splat, keyword hash and block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArgSpec {
+    pub lead: usize,
+    pub optional: usize,
+    pub splat: bool,
+    pub trailing: usize,
+    pub keywords: bool,
+    pub block: bool
+}
+
+impl ArgSpec {
+    // Panics on a malformed spec, same as rb_scan_args aborting on one
+    pub fn parse(spec: &str) -> Self {
+        let mut chars = spec.chars().peekable();
+        let mut arg_spec = ArgSpec { lead: 0, optional: 0, splat: false, trailing: 0, keywords: false, block: false };
+
+        if let Some(lead) = chars.peek().and_then(|c| c.to_digit(10)) {
+            arg_spec.lead = lead as usize;
+            chars.next();
+            if let Some(optional) = chars.peek().and_then(|c| c.to_digit(10)) {
+                arg_spec.optional = optional as usize;
+                chars.next();
+            }
+        }
+        if chars.peek() == Some(&'*') {
+            arg_spec.splat = true;
+            chars.next();
+        }
+        if let Some(trailing) = chars.peek().and_then(|c| c.to_digit(10)) {
+            arg_spec.trailing = trailing as usize;
+            chars.next();
+        }
+        if chars.peek() == Some(&':') {
+            arg_spec.keywords = true;
+            chars.next();
+        }
+        if chars.peek() == Some(&'&') {
+            arg_spec.block = true;
+            chars.next();
+        }
+        if chars.next().is_some() {
+            panic!("bad scan arg format: {}", spec);
+        }
+        arg_spec
+    }
+
+    pub fn required(&self) -> usize {
+        self.lead + self.trailing
+    }
+
+    // Maximum number of positional arguments, None when there is a splat
+    pub fn maximum(&self) -> Option<usize> {
+        if self.splat { None } else { Some(self.required() + self.optional) }
+    }
+}
+
+// Arguments of an argc/argv (-1 arity) method split up according to an `ArgSpec`:
+//
+//     extern "C" fn join(argc: c_int, argv: *const VALUE, this: VALUE) -> VALUE {
+//         catch_errors(|| {
+//             let args = unsafe { Args::scan(argc, argv, "11*") }?;
+//             let first: String = args.required(0)?;
+//             let separator = args.optional(0)?.unwrap_or(",".to_string());
+//             let rest: Vec<String> = args.splat()?;
+//             ...
+//         })
+//     }
+pub struct Args {
+    spec: ArgSpec,
+    lead: Vec<VALUE>,
+    optional: Vec<VALUE>,
+    splat: Vec<VALUE>,
+    trailing: Vec<VALUE>,
+    keywords: Option<VALUE>,
+    block: Option<Proc>
+}
+
+impl Args {
+    // Splits argv up, raising ArgumentError through `Err` when the number of arguments doesn't fit the spec.
+    // `argv` has to point to `argc` VALUEs, as ruby passes them to an argc/argv method.
+    pub unsafe fn scan(argc: ::libc::c_int, argv: *const VALUE, spec: &str) -> Result<Args, RubyException> {
+        Args::scan_spec(argc, argv, ArgSpec::parse(spec))
+    }
+
+    // Same as `scan` with an already parsed spec
+    pub unsafe fn scan_spec(argc: ::libc::c_int, argv: *const VALUE, spec: ArgSpec) -> Result<Args, RubyException> {
+        let values = if argc > 0 { slice::from_raw_parts(argv, argc as usize) } else { &[] };
+        Args::from_slice_spec(values, spec)
+    }
+
+    pub fn from_slice(values: &[VALUE], spec: &str) -> Result<Args, RubyException> {
+        Args::from_slice_spec(values, ArgSpec::parse(spec))
+    }
+
+    pub fn from_slice_spec(values: &[VALUE], spec: ArgSpec) -> Result<Args, RubyException> {
+        let mut values = values.to_vec();
+
+        // Trailing hash gives up its Symbol keys as keywords, but only when it isn't needed for
+        // a required argument. Whatever isn't keyed by a Symbol stays a positional argument.
+        let mut keywords = None;
+        if spec.keywords && values.len() > spec.required() {
+            let last = values[values.len() - 1];
+            if RubyType::from_value(last) == RubyType::Hash {
+                let (symbol_keys, other_keys) = KwArgs::extract(Hash::from_value_unchecked(last));
+                if symbol_keys.is_some() {
+                    keywords = symbol_keys.map(|hash| hash.to_value());
+                    values.pop();
+                    values.extend(other_keys.map(|hash| hash.to_value()));
+                }
+            }
+        }
+
+        let too_many = spec.maximum().map_or(false, |maximum| values.len() > maximum);
+        if values.len() < spec.required() || too_many {
+            return Err(arity_error(values.len(), spec));
+        }
+
+        let optional_given = ::std::cmp::min(spec.optional, values.len() - spec.required());
+        let splat_end = values.len() - spec.trailing;
+        let (lead, rest) = values.split_at(spec.lead);
+        let (optional, rest) = rest.split_at(optional_given);
+        let (splat, trailing) = rest.split_at(splat_end - spec.lead - optional_given);
+
+        let block = if spec.block { block_proc() } else { None };
+
+        Ok(Args {
+            spec: spec,
+            lead: lead.to_vec(),
+            optional: optional.to_vec(),
+            splat: splat.to_vec(),
+            trailing: trailing.to_vec(),
+            keywords: keywords,
+            block: block
+        })
+    }
+
+    pub fn spec(&self) -> ArgSpec {
+        self.spec
+    }
+
+    // Number of positional arguments given, keyword hash excluded
+    pub fn len(&self) -> usize {
+        self.lead.len() + self.optional.len() + self.splat.len() + self.trailing.len()
+    }
+
+    pub fn required<T>(&self, index: usize) -> Result<T, ConversionError> where T: FromValue {
+        convert_at(self.lead[index], index)
+    }
+
+    // None when the optional argument wasn't passed
+    pub fn optional<T>(&self, index: usize) -> Result<Option<T>, ConversionError> where T: FromValue {
+        assert!(index < self.spec.optional, "optional argument {} not in spec", index);
+        match self.optional.get(index) {
+            Some(&value) => convert_at(value, self.spec.lead + index).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    pub fn splat<T>(&self) -> Result<Vec<T>, ConversionError> where T: FromValue {
+        let offset = self.lead.len() + self.optional.len();
+        self.splat.iter().enumerate().map(|(index, &value)| convert_at(value, offset + index)).collect()
+    }
+
+    pub fn trailing<T>(&self, index: usize) -> Result<T, ConversionError> where T: FromValue {
+        let offset = self.lead.len() + self.optional.len() + self.splat.len();
+        convert_at(self.trailing[index], offset + index)
+    }
+
+    pub fn keywords(&self) -> Option<Hash> {
+        self.keywords.map(Hash::from_value_unchecked)
+    }
+
+    // Checks the keyword hash against the declared keywords, see `KwArgs`
+    pub fn kwargs(&self, required: &[&str], optional: &[&str]) -> Result<KwArgs, RubyException> {
+        KwArgs::parse(self.keywords(), required, optional)
+    }
+
+    // Block passed to the method
+    pub fn block(&self) -> Option<&Proc> {
+        self.block.as_ref()
+    }
+}
+
+fn convert_at<T>(value: VALUE, position: usize) -> Result<T, ConversionError> where T: FromValue {
+    T::try_from_value(value).map_err(|error| error.at_position(position))
+}
+
+// Lets ruby build its own "wrong number of arguments (given n, expected m..k)" ArgumentError
+fn arity_error(given: usize, spec: ArgSpec) -> RubyException {
+    // UNLIMITED_ARGUMENTS
+    let maximum = spec.maximum().map_or(-1, |maximum| maximum as i32);
+    let raised = protect(|| {
+        unsafe { rb_error_arity(given as i32, spec.required() as i32, maximum) };
+        RUBY_Qnil as VALUE
+    });
+    match raised {
+        Err(exception) => exception,
+        Ok(_) => unreachable!("rb_error_arity returned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ruby::VALUE;
+    use macros::INT2FIX;
+    use super::{ArgSpec, Args};
+
+    fn fixnums(count: i64) -> Vec<VALUE> {
+        (1..count + 1).map(INT2FIX).collect()
+    }
+
+    #[test]
+    fn parses_full_spec() {
+        let spec = ArgSpec::parse("12*1:&");
+        assert_eq!(spec, ArgSpec { lead: 1, optional: 2, splat: true, trailing: 1, keywords: true, block: true });
+        assert_eq!(spec.required(), 2);
+        assert_eq!(spec.maximum(), None);
+    }
+
+    #[test]
+    fn parses_partial_specs() {
+        assert_eq!(ArgSpec::parse("2").maximum(), Some(2));
+        assert_eq!(ArgSpec::parse("11").maximum(), Some(2));
+        let spec = ArgSpec::parse("*:");
+        assert_eq!((spec.lead, spec.splat, spec.keywords, spec.block), (0, true, true, false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_malformed_spec() {
+        ArgSpec::parse("1*x");
+    }
+
+    #[test]
+    fn splits_lead_optional_splat_and_trailing() {
+        let values = fixnums(5);
+        let args = Args::from_slice(&values, "11*1").unwrap();
+        assert_eq!(args.len(), 5);
+        assert_eq!(args.lead, &values[..1]);
+        assert_eq!(args.optional, &values[1..2]);
+        assert_eq!(args.splat, &values[2..4]);
+        assert_eq!(args.trailing, &values[4..]);
+    }
+
+    #[test]
+    fn fills_optional_before_splat() {
+        let values = fixnums(3);
+        let args = Args::from_slice(&values, "11*1").unwrap();
+        assert_eq!((args.lead.len(), args.optional.len(), args.splat.len()), (1, 1, 0));
+        assert_eq!(args.trailing, &values[2..]);
+    }
+
+    #[test]
+    fn leaves_optional_out_with_required_only() {
+        let values = fixnums(2);
+        let args = Args::from_slice(&values, "11*1").unwrap();
+        assert_eq!(args.lead, &values[..1]);
+        assert!(args.optional.is_empty() && args.splat.is_empty());
+        assert_eq!(args.trailing, &values[1..]);
+    }
+
+    #[test]
+    fn empty_splat() {
+        let values = fixnums(1);
+        let args = Args::from_slice(&values, "1*").unwrap();
+        assert_eq!(args.lead, values);
+        assert!(args.splat.is_empty() && args.keywords.is_none() && args.block.is_none());
+    }
+}
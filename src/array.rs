@@ -1,5 +1,6 @@
 use ruby::*;
-use super::{cast_str, ToValue, FromValue, RubyType};
+use super::{Call, ToValue, FromValue, RubyType, ConversionError, RubyException, Range};
+use super::bytes::lossy_string;
 use std::fmt;
 
 //
@@ -98,23 +99,29 @@ impl Array {
     //     unsafe { (*(*r_array)._as.heap()) }.len as usize
     // }
     pub fn len(&self) -> usize {
-        let value : Option<i64> =  FromValue::from_value(unsafe { rb_funcall(self.val, rb_intern(cast_str("size\x00")), 0) });
-        match value {
-            Some(len) => len as usize,
-            _ => panic!("Unexpected result of array.size")
+        match self.call::<i64>(id!("size"), ()) {
+            Ok(len) => len as usize,
+            Err(exception) => panic!("Unexpected result of array.size: {}", exception)
         }
     }
 
     pub fn entry(&self, index: usize) -> VALUE {
         unsafe { rb_ary_entry(self.to_value(), index as i64) }
     }
+
+    // Elements in `range` (e.g. `1..3`, `2..` or a ruby Range with negative ends), like Array#[].
+    // None when the range starts outside of the array.
+    pub fn slice<R>(&self, range: R) -> Result<Option<Array>, RubyException> where R: Into<Range> {
+        let selected = range.into().start_len(self.len())?;
+        Ok(selected.map(|(start, len)| Array { val: unsafe { rb_ary_subseq(self.val, start as i64, len as i64) } }))
+    }
 }
 
 impl FromValue for Array {
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::Array => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::Array => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Array, value))
         }
     }
     fn from_value_unchecked(value: VALUE) -> Self {
@@ -159,6 +166,6 @@ impl IntoIterator for Array {
 
 impl fmt::Debug for Array {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Array({})", String::from_value_unchecked(unsafe { rb_inspect(self.val) }) )
+        write!(f, "Array({})", lossy_string(unsafe { rb_inspect(self.val) }) )
     }
 }
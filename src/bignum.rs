@@ -0,0 +1,177 @@
+use ruby::*;
+use super::{ToValue, FromValue, RubyType, ConversionError};
+use super::bytes::lossy_string;
+use std::fmt;
+use std::mem;
+use std::ptr;
+
+// pub fn rb_integer_pack(val: VALUE, words: *mut ::libc::c_void, numwords: size_t, wordsize: size_t,
+//                        nails: size_t, flags: ::libc::c_int) -> ::libc::c_int;
+// pub fn rb_integer_unpack(words: *const ::libc::c_void, numwords: size_t, wordsize: size_t,
+//                          nails: size_t, flags: ::libc::c_int) -> VALUE;
+// pub fn rb_absint_size(val: VALUE, nlz_bits_ret: *mut ::libc::c_int) -> size_t;
+// pub fn rb_absint_numwords(val: VALUE, word_numbits: size_t, nlz_bits_ret: *mut size_t) -> size_t;
+
+// INTEGER_PACK_* flags of bignum.h, not part of the bindings
+const INTEGER_PACK_MSWORD_FIRST: i32 = 0x01;
+const INTEGER_PACK_LSWORD_FIRST: i32 = 0x02;
+const INTEGER_PACK_NATIVE: i32 = 0x40;
+const INTEGER_PACK_2COMP: i32 = 0x80;
+const INTEGER_PACK_NEGATIVE: i32 = 0x200;
+
+// Order of the words in a slice exchanged with a Bignum. The bytes of every word are in native
+// order, as rust stores them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordOrder {
+    // Least significant word first
+    LittleEndian,
+    // Most significant word first
+    BigEndian
+}
+
+impl WordOrder {
+    fn pack_flags(self) -> i32 {
+        match self {
+            WordOrder::LittleEndian => INTEGER_PACK_LSWORD_FIRST | INTEGER_PACK_NATIVE,
+            WordOrder::BigEndian => INTEGER_PACK_MSWORD_FIRST | INTEGER_PACK_NATIVE
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+// Unsigned integers usable as the words of a Bignum. rb_integer_pack writes arbitrary bytes into
+// them, so the trait is sealed to the types for which any bit pattern is valid.
+pub trait Word: Copy + Default + sealed::Sealed {}
+
+macro_rules! words {
+    ($($ty:ident),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl Word for $ty {}
+        )*
+    }
+}
+
+words!(u8, u16, u32, u64, usize);
+
+// Integer of any size. Ruby decides on Fixnum or Bignum by magnitude, so both are accepted:
+//
+//     let id = Bignum::from_words(&[0xdead_beef_u32, 0xcafe_babe, 1], WordOrder::LittleEndian, false);
+//     let bytes: Vec<u8> = id.to_words(WordOrder::BigEndian);
+pub struct Bignum {
+    val: VALUE
+}
+
+impl Bignum {
+    // Integer whose magnitude is `words`, negated when `negative`
+    pub fn from_words<W>(words: &[W], order: WordOrder, negative: bool) -> Bignum where W: Word {
+        let flags = order.pack_flags() | if negative { INTEGER_PACK_NEGATIVE } else { 0 };
+        let val = unsafe {
+            rb_integer_unpack(words.as_ptr() as *const ::libc::c_void, words.len() as size_t,
+                              mem::size_of::<W>() as size_t, 0, flags)
+        };
+        Bignum { val: val }
+    }
+
+    // Magnitude of the integer in as few words as it takes (none for zero), see `is_negative` for the sign
+    pub fn to_words<W>(&self, order: WordOrder) -> Vec<W> where W: Word {
+        let word_bits = mem::size_of::<W>() * 8;
+        let count = unsafe { rb_absint_numwords(self.val, word_bits as size_t, ptr::null_mut()) } as usize;
+        let mut words = vec![W::default(); count];
+        unsafe {
+            rb_integer_pack(self.val, words.as_mut_ptr() as *mut ::libc::c_void, count as size_t,
+                            mem::size_of::<W>() as size_t, 0, order.pack_flags());
+        }
+        words
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.sign() < 0
+    }
+
+    // -1, 0 or 1
+    pub fn sign(&self) -> i32 {
+        // Packing into no words at all still reports the sign (as overflow, ±2, unless zero)
+        let sign = unsafe { rb_integer_pack(self.val, ptr::null_mut(), 0, 1, 0, WordOrder::LittleEndian.pack_flags()) };
+        sign.signum()
+    }
+
+    // Number of bytes of the magnitude
+    pub fn byte_size(&self) -> usize {
+        unsafe { rb_absint_size(self.val, ptr::null_mut()) as usize }
+    }
+
+    // Number of significant bits of the magnitude, 0 for zero
+    pub fn bit_length(&self) -> usize {
+        let mut leading_zeros = 0;
+        let bytes = unsafe { rb_absint_size(self.val, &mut leading_zeros) as usize };
+        bytes * 8 - leading_zeros as usize
+    }
+}
+
+impl FromValue for Bignum {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value)  {
+            RubyType::Fixnum | RubyType::Bignum => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Bignum, value).expecting_class("Integer"))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Bignum { val: value }
+    }
+}
+
+impl ToValue for Bignum {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for Bignum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bignum({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
+
+// i128 and u128 go through a single 16 byte word
+macro_rules! wide_integer_conversions {
+    ($($ty:ident => $flags:expr),*) => {
+        $(
+            impl FromValue for $ty {
+                // Panics when the value isn't an Integer or doesn't fit
+                fn from_value_unchecked(value: VALUE) -> Self {
+                    Self::try_from_value(value).unwrap_or_else(|error| panic!("{}", error))
+                }
+                fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+                    match RubyType::from_value(value) {
+                        RubyType::Fixnum | RubyType::Bignum => (),
+                        _ => return Err(ConversionError::new(RubyType::Fixnum, value))
+                    }
+                    let mut result: $ty = 0;
+                    let sign = unsafe {
+                        rb_integer_pack(value, &mut result as *mut $ty as *mut ::libc::c_void, 1,
+                                        mem::size_of::<$ty>() as size_t, 0, $flags)
+                    };
+                    // ±2 is overflow, a negative sign doesn't fit an unsigned magnitude
+                    let fits = sign.abs() < 2 && (sign >= 0 || $flags & INTEGER_PACK_2COMP != 0);
+                    if fits { Ok(result) } else { Err(ConversionError::out_of_range(value, stringify!($ty))) }
+                }
+            }
+
+            impl ToValue for $ty {
+                fn to_value(&self) -> VALUE {
+                    unsafe {
+                        rb_integer_unpack(self as *const $ty as *const ::libc::c_void, 1,
+                                          mem::size_of::<$ty>() as size_t, 0, $flags)
+                    }
+                }
+            }
+        )*
+    }
+}
+
+wide_integer_conversions!(i128 => INTEGER_PACK_LSWORD_FIRST | INTEGER_PACK_NATIVE | INTEGER_PACK_2COMP,
+                          u128 => INTEGER_PACK_LSWORD_FIRST | INTEGER_PACK_NATIVE);
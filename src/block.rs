@@ -0,0 +1,156 @@
+use ruby::*;
+use super::{protect, Array, Proc, FromValue, ToValue, IntoException, RubyException, TypedData, rust_panic_class, Id};
+use std::cell::Cell;
+use std::any::Any;
+use std::marker::PhantomData;
+use std::mem::transmute;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+// pub fn rb_block_call(arg1: VALUE, arg2: ID, arg3: ::libc::c_int, arg4: *const VALUE,
+//                      arg5: rb_block_call_func_t, arg6: VALUE) -> VALUE;
+// pub fn rb_block_given_p() -> ::libc::c_int;
+// pub fn rb_yield(arg1: VALUE) -> VALUE;
+// pub fn rb_yield_values2(n: ::libc::c_int, argv: *const VALUE) -> VALUE;
+// pub fn rb_yield_splat(arg1: VALUE) -> VALUE;
+// pub fn rb_block_proc() -> VALUE;
+// pub fn rb_enumeratorize_with_size(arg1: VALUE, arg2: VALUE, arg3: ::libc::c_int, arg4: *const VALUE,
+//                                   arg5: *mut ::std::option::Option<extern "C" fn() -> VALUE>) -> VALUE;
+
+// Computes Enumerator#size without iterating: size(receiver, args, enumerator)
+pub type EnumeratorSizeFn = extern "C" fn(VALUE, VALUE, VALUE) -> VALUE;
+
+// Closure passed to ruby as the block, along with a panic it may have raised
+struct RustBlock<F, A, R> {
+    func: F,
+    panic: Option<Box<dyn Any + Send>>,
+    _marker: PhantomData<fn(A) -> R>
+}
+
+// Handed to ruby as the block's data, pointing to the boxed `RustBlock` while `call_with_block` runs.
+// A method may keep its block (`@saved = blk`, define_method) and call it later, when the closure
+// and whatever it borrows are gone: the pointer is null by then and the call raises instead.
+struct BlockSlot {
+    block: Cell<*mut ::libc::c_void>
+}
+
+typed_data!(BlockSlot, "RustBlock");
+
+// Called by ruby for every yield. Nothing but C frames sits between this function and the
+// rb_protect in `call_with_block`, so raising from here to stop the iteration is safe:
+// the closure and the values it produced are already dropped by then.
+unsafe extern "C" fn rust_block_trampoline<F, A, R>(yielded_arg: VALUE, callback_arg: VALUE, argc: ::libc::c_int,
+                                                     argv: *const VALUE, _blockarg: VALUE) -> VALUE
+    where F: FnMut(A) -> R, A: FromValue, R: ToValue
+{
+    let block_ptr = TypedData::<BlockSlot>::get_ref_unchecked(callback_arg).block.get();
+    if block_ptr.is_null() {
+        let exception = rb_exc_new_str(rb_eRuntimeError, "rust block called after call_with_block returned".to_string().to_value());
+        rb_exc_raise(exception);
+    }
+    let block = &mut *(block_ptr as *mut RustBlock<F, A, R>);
+    let func = &mut block.func;
+    // `yield a, b` passes every value in argv but only `a` as yielded_arg
+    let yielded = if argc > 1 { rb_ary_new_from_values(argc as i64, argv) } else { yielded_arg };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        A::try_from_value(yielded).map(|arg| func(arg).to_value())
+    }));
+    let exception = match result {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error.raise(),
+        Err(payload) => {
+            // Stop the iteration, `call_with_block` resumes the panic once back in rust
+            block.panic = Some(payload);
+            rb_exc_new_str(rust_panic_class(), "panic in block".to_string().to_value())
+        }
+    };
+    rb_exc_raise(exception);
+    unreachable!()
+}
+
+// Calls method `name` on `recv` with a rust closure as the block:
+//
+//     let mut sum = 0;
+//     call_with_block(array.to_value(), "each", &[], |item: i64| { sum += item; Nil })?;
+//
+// Yielded values are converted with `FromValue` (raising TypeError inside the iteration on failure),
+// the closure's result is the value of the yield. Ruby exceptions come back as `Err`, panics in the
+// closure are resumed once ruby is out of the way. The closure is only called until this returns,
+// calling a block the method kept raises RuntimeError from then on.
+//
+// Several values yielded at once (`yield a, b`, e.g. each_with_index) arrive as one Array:
+//
+//     call_with_block(array.to_value(), "each_with_index", &[], |pair: Array| { ... })?;
+pub fn call_with_block<F, A, R>(recv: VALUE, name: &str, args: &[VALUE], block: F) -> Result<VALUE, RubyException>
+    where F: FnMut(A) -> R, A: FromValue, R: ToValue
+{
+    let id = Id::new(name).as_raw();
+    let mut block = Box::new(RustBlock { func: block, panic: None, _marker: PhantomData });
+    let block_ptr = &mut *block as *mut RustBlock<F, A, R> as *mut ::libc::c_void;
+    // Hidden, kept alive by the block while the method holds on to it
+    let slot = TypedData::wrap(0, BlockSlot { block: Cell::new(block_ptr) });
+    let result = protect(|| unsafe {
+        let trampoline: rb_block_call_func = rust_block_trampoline::<F, A, R>;
+        rb_block_call(recv, id, args.len() as i32, args.as_ptr(), Some(transmute(trampoline)), slot)
+    });
+    unsafe { TypedData::<BlockSlot>::get_ref_unchecked(slot) }.block.set(ptr::null_mut());
+    if let Some(payload) = block.panic.take() {
+        panic::resume_unwind(payload);
+    }
+    result
+}
+
+// Whether the method currently running was called with a block
+pub fn block_given() -> bool {
+    unsafe { rb_block_given_p() != 0 }
+}
+
+// Yields to the block of the method currently running. `break` or an exception in the block
+// comes back as `Err`, return it from the method to let ruby carry on with it.
+pub fn yield_value<T>(value: T) -> Result<VALUE, RubyException> where T: ToValue {
+    let value = value.to_value();
+    protect(|| unsafe { rb_yield(value) })
+}
+
+// yield a, b, c
+pub fn yield_values(values: &[VALUE]) -> Result<VALUE, RubyException> {
+    protect(|| unsafe { rb_yield_values2(values.len() as i32, values.as_ptr()) })
+}
+
+// yield *array
+pub fn yield_splat(values: Array) -> Result<VALUE, RubyException> {
+    protect(|| unsafe { rb_yield_splat(values.to_value()) })
+}
+
+// The block of the method currently running as a Proc, None without a block
+pub fn block_proc() -> Option<Proc> {
+    if block_given() {
+        Some(Proc::from_value_unchecked(unsafe { rb_block_proc() }))
+    } else {
+        None
+    }
+}
+
+// Enumerator calling `method` on `recv` with `args`, what core methods return when called without a block:
+//
+//     if !block_given() {
+//         return Ok(enumerator(this, "each_pair", &[]));
+//     }
+pub fn enumerator(recv: VALUE, method: &str, args: &[VALUE]) -> VALUE {
+    enumeratorize(recv, method, args, ptr::null_mut())
+}
+
+// Same as `enumerator`, with `size` answering Enumerator#size
+pub fn sized_enumerator(recv: VALUE, method: &str, args: &[VALUE], size: EnumeratorSizeFn) -> VALUE {
+    // The C parameter is `rb_enumerator_size_func *`, a plain function pointer,
+    // bindgen turned it into a pointer to an optional function pointer
+    let size_fn = unsafe { transmute::<EnumeratorSizeFn, *mut Option<extern "C" fn() -> VALUE>>(size) };
+    enumeratorize(recv, method, args, size_fn)
+}
+
+fn enumeratorize(recv: VALUE, method: &str, args: &[VALUE], size_fn: *mut Option<extern "C" fn() -> VALUE>) -> VALUE {
+    unsafe {
+        let method = rb_id2sym(Id::new(method).as_raw());
+        rb_enumeratorize_with_size(recv, method, args.len() as i32, args.as_ptr(), size_fn)
+    }
+}
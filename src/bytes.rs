@@ -0,0 +1,76 @@
+use ruby::*;
+use macros::*;
+use super::{ToValue, FromValue, RubyType, ConversionError};
+use std::ops::{Deref, DerefMut};
+use std::slice;
+
+// pub fn rb_str_new(arg1: *const ::libc::c_char, arg2: ::libc::c_long) -> VALUE;
+
+// Contents of a String as raw bytes, whatever their encoding. Converts back to a binary
+// (ASCII-8BIT) String, so payloads with NUL bytes or invalid UTF-8 make the round trip intact:
+//
+//     let Bytes(payload) = Bytes::try_from_value(value)?;
+//     Bytes(compress(&payload)).to_value()
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+// Bytes of a String VALUE, valid until the string is modified or collected
+pub(crate) unsafe fn string_bytes<'a>(value: VALUE) -> &'a [u8] {
+    let len = RSTRING_LEN(value) as usize;
+    if len == 0 { &[] } else { slice::from_raw_parts(RSTRING_PTR(value), len) }
+}
+
+// Contents of a String VALUE with invalid UTF-8 replaced by U+FFFD, for messages and inspect output
+// which shouldn't fail over a binary string
+pub(crate) fn lossy_string(value: VALUE) -> String {
+    String::from_utf8_lossy(unsafe { string_bytes(value) }).into_owned()
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Bytes {
+    fn from(bytes: &'a [u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl Deref for Bytes {
+    type Target = Vec<u8>;
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl FromValue for Bytes {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value)  {
+            RubyType::String => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::String, value))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Bytes(unsafe { string_bytes(value) }.to_vec())
+    }
+}
+
+impl ToValue for Bytes {
+    fn to_value(&self) -> VALUE {
+        unsafe { rb_str_new(self.0.as_ptr() as *const i8, self.0.len() as i64) }
+    }
+}
@@ -0,0 +1,111 @@
+use ruby::*;
+use super::{protect, FromValue, ToValue, RubyException, Id, StaticId};
+
+// pub fn rb_funcallv(arg1: VALUE, arg2: ID, arg3: ::libc::c_int, arg4: *const VALUE) -> VALUE;
+// pub fn rb_funcallv_public(arg1: VALUE, arg2: ID, arg3: ::libc::c_int, arg4: *const VALUE) -> VALUE;
+// pub fn rb_check_funcall(arg1: VALUE, arg2: ID, arg3: ::libc::c_int, arg4: *const VALUE) -> VALUE;
+// pub fn rb_respond_to(arg1: VALUE, arg2: ID) -> ::libc::c_int;
+
+// Method name, either interned on every call or taken from a cache
+pub trait IntoId {
+    fn into_id(self) -> Id;
+}
+
+impl<'a> IntoId for &'a str {
+    fn into_id(self) -> Id {
+        Id::new(self)
+    }
+}
+
+impl IntoId for Id {
+    fn into_id(self) -> Id {
+        self
+    }
+}
+
+impl<'a> IntoId for &'a StaticId {
+    fn into_id(self) -> Id {
+        self.id()
+    }
+}
+
+// Arguments of a method call: a tuple of `ToValue`s, or a slice of VALUEs
+pub trait Arguments {
+    fn into_values(self) -> Vec<VALUE>;
+}
+
+impl<'a> Arguments for &'a [VALUE] {
+    fn into_values(self) -> Vec<VALUE> {
+        self.to_vec()
+    }
+}
+
+impl Arguments for Vec<VALUE> {
+    fn into_values(self) -> Vec<VALUE> {
+        self
+    }
+}
+
+macro_rules! tuple_arguments {
+    ($($name:ident),*) => {
+        impl<$($name),*> Arguments for ($($name,)*) where $($name: ToValue),* {
+            #[allow(non_snake_case)]
+            fn into_values(self) -> Vec<VALUE> {
+                let ($($name,)*) = self;
+                vec![$($name.to_value()),*]
+            }
+        }
+    }
+}
+
+tuple_arguments!();
+tuple_arguments!(A);
+tuple_arguments!(A, B);
+tuple_arguments!(A, B, C);
+tuple_arguments!(A, B, C, D);
+tuple_arguments!(A, B, C, D, E);
+tuple_arguments!(A, B, C, D, E, F);
+tuple_arguments!(A, B, C, D, E, F, G);
+tuple_arguments!(A, B, C, D, E, F, G, H);
+
+// Method calls on any ruby value, with the result converted through `FromValue`:
+//
+//     let size = array.call::<i64>(id!("size"), ())?;
+//     let joined = array.call::<String>("join", (", ".to_string(),))?;
+//
+// Exceptions raised by the method and failed conversions of the result both come back as `Err`.
+pub trait Call: ToValue {
+    fn call<R>(&self, name: impl IntoId, args: impl Arguments) -> Result<R, RubyException> where R: FromValue {
+        let (recv, id, args) = (self.to_value(), name.into_id().as_raw(), args.into_values());
+        let value = protect(|| unsafe { rb_funcallv(recv, id, args.len() as i32, args.as_ptr()) })?;
+        Ok(R::try_from_value(value)?)
+    }
+
+    // Same as `call` but respects visibility, like calling the method from ruby with an explicit receiver
+    fn call_public<R>(&self, name: impl IntoId, args: impl Arguments) -> Result<R, RubyException> where R: FromValue {
+        let (recv, id, args) = (self.to_value(), name.into_id().as_raw(), args.into_values());
+        let value = protect(|| unsafe { rb_funcallv_public(recv, id, args.len() as i32, args.as_ptr()) })?;
+        Ok(R::try_from_value(value)?)
+    }
+
+    // Ok(None) when the receiver doesn't respond to the method
+    fn try_call<R>(&self, name: impl IntoId, args: impl Arguments) -> Result<Option<R>, RubyException> where R: FromValue {
+        let (recv, id, args) = (self.to_value(), name.into_id().as_raw(), args.into_values());
+        let value = protect(|| unsafe { rb_check_funcall(recv, id, args.len() as i32, args.as_ptr()) })?;
+        if value == RUBY_Qundef as VALUE {
+            return Ok(None);
+        }
+        Ok(Some(R::try_from_value(value)?))
+    }
+
+    // respond_to?, false when a custom respond_to? raises
+    fn respond_to(&self, name: impl IntoId) -> bool {
+        let (recv, id) = (self.to_value(), name.into_id().as_raw());
+        match protect(|| unsafe { rb_respond_to(recv, id) as VALUE }) {
+            Ok(responds) => responds != 0,
+            Err(_) => false
+        }
+    }
+}
+
+impl<T> Call for T where T: ToValue {}
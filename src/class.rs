@@ -0,0 +1,181 @@
+use ruby::*;
+use super::{ToValue, RubyCallable};
+use std::ffi::CString;
+
+// pub fn rb_define_class(arg1: *const ::libc::c_char, arg2: VALUE) -> VALUE;
+// pub fn rb_define_module(arg1: *const ::libc::c_char) -> VALUE;
+// pub fn rb_define_class_under(arg1: VALUE, arg2: *const ::libc::c_char, arg3: VALUE) -> VALUE;
+// pub fn rb_define_module_under(arg1: VALUE, arg2: *const ::libc::c_char) -> VALUE;
+// pub fn rb_include_module(arg1: VALUE, arg2: VALUE) -> ();
+// pub fn rb_extend_object(arg1: VALUE, arg2: VALUE) -> ();
+// pub fn rb_prepend_module(arg1: VALUE, arg2: VALUE) -> ();
+// pub fn rb_define_const(arg1: VALUE, arg2: *const ::libc::c_char, arg3: VALUE) -> ();
+
+type RubyFunction = extern "C" fn() -> VALUE;
+
+enum Definition {
+    Include(VALUE),
+    Extend(VALUE),
+    Prepend(VALUE),
+    Const(CString, VALUE),
+    Method(CString, RubyFunction, i32),
+    SingletonMethod(CString, RubyFunction, i32)
+}
+
+// Everything defined on a class or module once it exists, applied in the order it was declared
+struct Definitions {
+    list: Vec<Definition>
+}
+
+impl Definitions {
+    fn new() -> Self {
+        Definitions { list: Vec::new() }
+    }
+
+    fn push(&mut self, definition: Definition) {
+        self.list.push(definition)
+    }
+
+    fn apply(self, target: VALUE) {
+        for definition in self.list {
+            unsafe {
+                match definition {
+                    Definition::Include(module) => rb_include_module(target, module),
+                    Definition::Extend(module) => rb_extend_object(target, module),
+                    Definition::Prepend(module) => rb_prepend_module(target, module),
+                    Definition::Const(name, value) => rb_define_const(target, name.as_ptr(), value),
+                    Definition::Method(name, func, argc) => rb_define_method(target, name.as_ptr(), Some(func), argc),
+                    Definition::SingletonMethod(name, func, argc) => rb_define_singleton_method(target, name.as_ptr(), Some(func), argc)
+                }
+            }
+        }
+    }
+}
+
+// Builder methods shared by `Class` and `Module`, queued on their `definitions`
+macro_rules! definition_methods {
+    ($($builder:ident),*) => {
+        $(
+            impl $builder {
+                pub fn include(mut self, module: VALUE) -> Self {
+                    self.definitions.push(Definition::Include(module));
+                    self
+                }
+
+                pub fn extend(mut self, module: VALUE) -> Self {
+                    self.definitions.push(Definition::Extend(module));
+                    self
+                }
+
+                pub fn prepend(mut self, module: VALUE) -> Self {
+                    self.definitions.push(Definition::Prepend(module));
+                    self
+                }
+
+                pub fn const_set<T>(mut self, name: &str, value: T) -> Self where T: ToValue {
+                    self.definitions.push(Definition::Const(CString::new(name).unwrap(), value.to_value()));
+                    self
+                }
+
+                // `func` must not unwind: define it with `ruby_method!` or guard its body, see `RubyCallable`
+                pub fn method<F>(mut self, name: &str, func: F) -> Self where F: RubyCallable {
+                    self.definitions.push(Definition::Method(CString::new(name).unwrap(), func.as_function_ptr(), func.arity()));
+                    self
+                }
+
+                pub fn singleton_method<F>(mut self, name: &str, func: F) -> Self where F: RubyCallable {
+                    self.definitions.push(Definition::SingletonMethod(CString::new(name).unwrap(), func.as_function_ptr(), func.arity()));
+                    self
+                }
+            }
+        )*
+    }
+}
+
+// Builds a ruby class:
+//
+//     let point = Class::define("Point")
+//         .under(my_mod)
+//         .include(unsafe { rb_mComparable })
+//         .const_set("DIMENSIONS", 2)
+//         .method("x", point_x as extern "C" fn(VALUE) -> VALUE)
+//         .build();
+pub struct Class {
+    name: CString,
+    superclass: VALUE,
+    namespace: Option<VALUE>,
+    definitions: Definitions
+}
+
+impl Class {
+    pub fn define(name: &str) -> Self {
+        Class {
+            name: CString::new(name).unwrap(),
+            superclass: unsafe { rb_cObject },
+            namespace: None,
+            definitions: Definitions::new()
+        }
+    }
+
+    // Object by default
+    pub fn superclass(mut self, superclass: VALUE) -> Self {
+        self.superclass = superclass;
+        self
+    }
+
+    // Defines the class inside a module or class instead of at the top level
+    pub fn under(mut self, namespace: VALUE) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    // Defines (or reopens) the class and applies everything declared on the builder
+    pub fn build(self) -> VALUE {
+        let class = unsafe {
+            match self.namespace {
+                Some(namespace) => rb_define_class_under(namespace, self.name.as_ptr(), self.superclass),
+                None => rb_define_class(self.name.as_ptr(), self.superclass)
+            }
+        };
+        self.definitions.apply(class);
+        class
+    }
+}
+
+// Builds a ruby module, same as `Class` minus the superclass:
+//
+//     let my_mod = Module::define("TestRust").build();
+pub struct Module {
+    name: CString,
+    namespace: Option<VALUE>,
+    definitions: Definitions
+}
+
+impl Module {
+    pub fn define(name: &str) -> Self {
+        Module {
+            name: CString::new(name).unwrap(),
+            namespace: None,
+            definitions: Definitions::new()
+        }
+    }
+
+    pub fn under(mut self, namespace: VALUE) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    // Defines (or reopens) the module and applies everything declared on the builder
+    pub fn build(self) -> VALUE {
+        let module = unsafe {
+            match self.namespace {
+                Some(namespace) => rb_define_module_under(namespace, self.name.as_ptr()),
+                None => rb_define_module(self.name.as_ptr())
+            }
+        };
+        self.definitions.apply(module);
+        module
+    }
+}
+
+definition_methods!(Class, Module);
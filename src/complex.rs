@@ -0,0 +1,102 @@
+use ruby::*;
+use super::{ToValue, FromValue, RubyType, ConversionError, Numeric};
+use super::bytes::lossy_string;
+use std::convert::TryFrom;
+use std::fmt;
+
+// pub fn rb_complex_new(arg1: VALUE, arg2: VALUE) -> VALUE;
+// pub fn rb_complex_polar(arg1: VALUE, arg2: VALUE) -> VALUE;
+
+// struct RComplex, not part of the bindings
+#[repr(C)]
+struct RComplex {
+    basic: Struct_RBasic,
+    real: VALUE,
+    imag: VALUE
+}
+
+// Ruby Complex. Its parts are any real Numeric (Integer, Float, Rational):
+//
+//     let z = Complex::polar(2.0, PI / 2.0);
+//     let (real, imag) = <(f64, f64)>::try_from(z)?;
+pub struct Complex {
+    val: VALUE
+}
+
+impl Complex {
+    pub fn new(real: f64, imag: f64) -> Complex {
+        Complex { val: unsafe { rb_complex_new(real.to_value(), imag.to_value()) } }
+    }
+
+    // From absolute value and argument (in radians)
+    pub fn polar(abs: f64, arg: f64) -> Complex {
+        Complex { val: unsafe { rb_complex_polar(abs.to_value(), arg.to_value()) } }
+    }
+
+    // Real part as e.g. i64, Rational or `Numeric` (any of them as a float)
+    pub fn real<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(self.parts().0)
+    }
+
+    pub fn imag<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(self.parts().1)
+    }
+
+    fn parts(&self) -> (VALUE, VALUE) {
+        let complex = unsafe { &*(self.val as *const RComplex) };
+        (complex.real, complex.imag)
+    }
+}
+
+impl From<(f64, f64)> for Complex {
+    fn from((real, imag): (f64, f64)) -> Self {
+        Complex::new(real, imag)
+    }
+}
+
+impl From<(i64, i64)> for Complex {
+    fn from((real, imag): (i64, i64)) -> Self {
+        Complex { val: unsafe { rb_complex_new(real.to_value(), imag.to_value()) } }
+    }
+}
+
+// (real, imag), both parts need to be Integers
+impl TryFrom<Complex> for (i64, i64) {
+    type Error = ConversionError;
+    fn try_from(complex: Complex) -> Result<Self, ConversionError> {
+        Ok((complex.real()?, complex.imag()?))
+    }
+}
+
+// (real, imag), whatever kind of Numeric the parts are
+impl TryFrom<Complex> for (f64, f64) {
+    type Error = ConversionError;
+    fn try_from(complex: Complex) -> Result<Self, ConversionError> {
+        let (real, imag): (Numeric, Numeric) = (complex.real()?, complex.imag()?);
+        Ok((real.0, imag.0))
+    }
+}
+
+impl FromValue for Complex {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value)  {
+            RubyType::Complex => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Complex, value))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Complex { val: value }
+    }
+}
+
+impl ToValue for Complex {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Complex({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
@@ -0,0 +1,167 @@
+use ruby::*;
+use super::{RubyType, ToValue, IntoException};
+use super::bytes::lossy_string;
+use std::ffi::CStr;
+use std::error::Error;
+use std::fmt;
+
+// Describes why a VALUE could not be converted into a Rust type.
+// Mirrors the message of Ruby's own TypeError:
+//     wrong argument type Hash (expected Array)
+// or, for an Integer which doesn't fit the Rust type, RangeError:
+//     integer 300 too big to convert to `u8'
+// or, for a String which isn't valid in (or can't be transcoded to) UTF-8, EncodingError.
+#[derive(Debug, Clone)]
+pub struct ConversionError {
+    // Boxed to keep the `Err` of conversion results a single pointer
+    details: Box<Details>
+}
+
+#[derive(Debug, Clone)]
+struct Details {
+    expected: RubyType,
+    expected_class: Option<&'static str>,
+    actual: RubyType,
+    class_name: String,
+    position: Option<usize>,
+    keyword: Option<String>,
+    reason: Reason
+}
+
+#[derive(Debug, Clone)]
+enum Reason {
+    WrongType,
+    // Digits of the Integer and the Rust type it doesn't fit in
+    OutOfRange(String, &'static str),
+    // Why the string's bytes couldn't be read as UTF-8
+    Encoding(String)
+}
+
+impl ConversionError {
+    pub fn new(expected: RubyType, value: VALUE) -> Self {
+        let class_name = unsafe { CStr::from_ptr(rb_obj_classname(value)) }.to_string_lossy().into_owned();
+        ConversionError {
+            details: Box::new(Details {
+                expected: expected,
+                expected_class: None,
+                actual: RubyType::from_value(value),
+                class_name: class_name,
+                position: None,
+                keyword: None,
+                reason: Reason::WrongType
+            })
+        }
+    }
+
+    // Integer `value` is outside of the range of `target` (e.g. "u8")
+    pub fn out_of_range(value: VALUE, target: &'static str) -> Self {
+        let digits = lossy_string(unsafe { rb_obj_as_string(value) });
+        let mut error = ConversionError::new(RubyType::from_value(value), value);
+        error.details.reason = Reason::OutOfRange(digits, target);
+        error
+    }
+
+    // String `value` couldn't be converted to UTF-8, `message` tells why
+    pub fn bad_encoding(value: VALUE, message: String) -> Self {
+        let mut error = ConversionError::new(RubyType::String, value);
+        error.details.reason = Reason::Encoding(message);
+        error
+    }
+
+    // Names the expected class in the message, for values where the RubyType alone is too vague (e.g. Data)
+    pub fn expecting_class(mut self, class_name: &'static str) -> Self {
+        self.details.expected_class = Some(class_name);
+        self
+    }
+
+    // Records the (zero based) position of the argument which failed to convert
+    pub fn at_position(mut self, position: usize) -> Self {
+        self.details.position = Some(position);
+        self
+    }
+
+    // Records the name of the keyword argument which failed to convert
+    pub fn at_keyword(mut self, keyword: &str) -> Self {
+        self.details.keyword = Some(keyword.to_string());
+        self
+    }
+
+    pub fn expected(&self) -> RubyType {
+        self.details.expected
+    }
+
+    pub fn actual(&self) -> RubyType {
+        self.details.actual
+    }
+
+    pub fn class_name(&self) -> &str {
+        &self.details.class_name
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        self.details.position
+    }
+
+    pub fn keyword(&self) -> Option<&str> {
+        self.details.keyword.as_ref().map(|keyword| &keyword[..])
+    }
+
+    // Whether the value had the right type but didn't fit (RangeError rather than TypeError)
+    pub fn is_out_of_range(&self) -> bool {
+        match self.details.reason { Reason::OutOfRange(..) => true, _ => false }
+    }
+
+    // Whether the value was a String whose bytes couldn't be made UTF-8 (EncodingError rather than TypeError)
+    pub fn is_bad_encoding(&self) -> bool {
+        match self.details.reason { Reason::Encoding(_) => true, _ => false }
+    }
+
+    // Builds (but doesn't raise) a TypeError, RangeError or EncodingError instance carrying the message of this error
+    pub fn to_exception(&self) -> VALUE {
+        let message = self.to_string().to_value();
+        let class = match self.details.reason {
+            Reason::WrongType => unsafe { rb_eTypeError },
+            Reason::OutOfRange(..) => unsafe { rb_eRangeError },
+            Reason::Encoding(_) => unsafe { rb_eEncodingError }
+        };
+        unsafe { rb_exc_new_str(class, message) }
+    }
+
+}
+
+impl IntoException for ConversionError {
+    fn into_exception(self) -> VALUE {
+        self.to_exception()
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.details.reason {
+            Reason::WrongType => {
+                let expected = self.details.expected_class.unwrap_or(self.details.expected.name());
+                write!(f, "wrong argument type {} (expected {})", self.details.class_name, expected)?
+            }
+            Reason::OutOfRange(ref digits, target) => {
+                let size = if digits.starts_with('-') { "small" } else { "big" };
+                write!(f, "integer {} too {} to convert to `{}'", digits, size, target)?
+            }
+            Reason::Encoding(ref message) => write!(f, "{}", message)?
+        }
+        match (self.details.position, self.details.keyword.as_ref()) {
+            (Some(position), _) => write!(f, " for argument #{}", position + 1),
+            (None, Some(keyword)) => write!(f, " for keyword {}", keyword),
+            (None, None) => Ok(())
+        }
+    }
+}
+
+impl Error for ConversionError {
+    fn description(&self) -> &str {
+        match self.details.reason {
+            Reason::WrongType => "wrong argument type",
+            Reason::OutOfRange(..) => "integer out of range",
+            Reason::Encoding(_) => "string not convertible to UTF-8"
+        }
+    }
+}
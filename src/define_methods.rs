@@ -1,3 +1,6 @@
+use ruby::*;
+use std::ffi::CString;
+use std::mem::transmute;
 
 // Typesafe method definition, example on rb_define_method(VALUE klass, const char *name, VALUE (*func)(), int argc)
 // Goal: be able to define method in type safe maner.
@@ -6,41 +9,59 @@
 //     * Positive number of arguments, function definition looks like:
 //         pub extern "C" fn func(this: VALUE, arg1: VALUE, arg2: VALUE, arg3: VALUE) -> VALUE
 //     * -1 number of arguments:
-//         pub extern "C" fn func(argc: usize, argv: *const VALUE, this: VALUE) -> VALUE
+//         pub extern "C" fn func(argc: c_int, argv: *const VALUE, this: VALUE) -> VALUE
 //     * -2 arguments:
 //         pub extern "C" fn func(this: VALUE, args: VALUE) -> VALUE
+//       Not supported, it has the same signature as a method taking one argument.
 // Approach: Define a trait, variant over function type (arguments) and implement for each number of arguments (up to 17)
+// The receiver is always passed to the function, so there is no impl for `extern "C" fn() -> VALUE`.
+//
+// Since Rust doesn't allow to automatically cast functions to their prototypes this looks ugly:
+//     (foo as extern "C" fn(VALUE, VALUE) -> VALUE).define_singleton_method(my_mod, "foo")
+//
+// Any extern "C" function of the right shape is accepted, guarded or not. Only the functions made by
+// `ruby_method!` (or whose body runs in `catch_errors`/`catch_panic`) turn panics into ruby
+// exceptions; a panic unwinding out of any other function into the VM is undefined behavior.
 
 
-trait RubyCallable: Sized {
+pub trait RubyCallable: Sized {
     // const number_of_args: i32;
+    // Number of arguments of the rust function, receiver included. -1 for the argc/argv form.
     fn number_of_args(&self) -> i32;
 
     fn as_function_ptr(&self) -> extern "C" fn() -> VALUE;
 
+    // Arity as ruby expects it: number of arguments excluding the receiver, or -1 for argc/argv
+    fn arity(&self) -> i32 {
+        match self.number_of_args() {
+            -1 => -1,
+            n => n - 1
+        }
+    }
+
     fn define_singleton_method(&self, object: VALUE, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_singleton_method(object, c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args() - 1); }
+        unsafe { rb_define_singleton_method(object, c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
     fn define_method(&self, class: VALUE, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args() -1 ); }
+        unsafe { rb_define_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
     fn define_private_method(&self, class: VALUE, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_private_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args() - 1); }
+        unsafe { rb_define_private_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
     fn define_protected_method(&self, class: VALUE, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_protected_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args() - 1); }
+        unsafe { rb_define_protected_method(class, c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
     fn define_module_function(&self, module: VALUE, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_module_function(module, c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args() - 1); }
+        unsafe { rb_define_module_function(module, c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
     fn define_global_function(&self, name: &str) {
         let c_name = CString::new(name).unwrap();
-        unsafe { rb_define_global_function(c_name.as_ptr(), Some(self.as_function_ptr()), self.number_of_args()); }
+        unsafe { rb_define_global_function(c_name.as_ptr(), Some(self.as_function_ptr()), self.arity()); }
     }
 }
 
@@ -58,7 +79,6 @@ macro_rules! ruby_callable_def {
     }
 }
 
-ruby_callable_def!(extern "C" fn () -> VALUE, 0);
 ruby_callable_def!(extern "C" fn (VALUE) -> VALUE, 1);
 ruby_callable_def!(extern "C" fn (VALUE, VALUE) -> VALUE, 2);
 ruby_callable_def!(extern "C" fn (VALUE, VALUE, VALUE) -> VALUE, 3);
@@ -76,14 +96,28 @@ ruby_callable_def!(extern "C" fn (VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALU
 ruby_callable_def!(extern "C" fn (VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE) -> VALUE, 15);
 ruby_callable_def!(extern "C" fn (VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE) -> VALUE, 16);
 ruby_callable_def!(extern "C" fn (VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE) -> VALUE, 17);
-ruby_callable_def!(extern "C" fn (usize, *const VALUE, VALUE) -> VALUE, -1);
+ruby_callable_def!(extern "C" fn (::libc::c_int, *const VALUE, VALUE) -> VALUE, -1);
 
 
-// Since Rust doesn't allow to automatically cast functions to their prototypes this looks ugly
-fn usage {
-    (foo as extern "C" fn(VALUE, VALUE) -> VALUE).define_singleton_method(my_mod, "foo")
-}
+#[cfg(test)]
+mod tests {
+    use ruby::VALUE;
+    use super::RubyCallable;
 
-pub extern "C" foo(this: VALUE, arg: VALUE) -> VALUE {
-    unimplemented!()
+    extern "C" fn receiver_only(_this: VALUE) -> VALUE { 0 }
+    extern "C" fn two_args(_this: VALUE, _a: VALUE, _b: VALUE) -> VALUE { 0 }
+    extern "C" fn variadic(_argc: ::libc::c_int, _argv: *const VALUE, _this: VALUE) -> VALUE { 0 }
+
+    #[test]
+    fn arity_excludes_receiver() {
+        assert_eq!((receiver_only as extern "C" fn(VALUE) -> VALUE).arity(), 0);
+        assert_eq!((two_args as extern "C" fn(VALUE, VALUE, VALUE) -> VALUE).arity(), 2);
+    }
+
+    #[test]
+    fn arity_of_argc_argv_form() {
+        let func = variadic as extern "C" fn(::libc::c_int, *const VALUE, VALUE) -> VALUE;
+        assert_eq!(func.number_of_args(), -1);
+        assert_eq!(func.arity(), -1);
+    }
 }
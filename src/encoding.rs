@@ -0,0 +1,165 @@
+use ruby::*;
+use macros::*;
+use super::{protect, ToValue, FromValue, RubyType, ConversionError};
+use super::bytes::string_bytes;
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+// Leading fields of OnigEncodingType (rb_encoding), the rest is never read from rust
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub(crate) struct rb_encoding {
+    precise_mbc_enc_len: *const ::libc::c_void,
+    name: *const ::libc::c_char,
+    max_enc_len: ::libc::c_int,
+    min_enc_len: ::libc::c_int
+}
+
+// ruby/encoding.h, not part of the bindings
+extern "C" {
+    pub(crate) fn rb_enc_find_index(name: *const ::libc::c_char) -> ::libc::c_int;
+    pub(crate) fn rb_enc_from_index(index: ::libc::c_int) -> *mut rb_encoding;
+    pub(crate) fn rb_enc_to_index(enc: *mut rb_encoding) -> ::libc::c_int;
+    pub(crate) fn rb_enc_get(obj: VALUE) -> *mut rb_encoding;
+    pub(crate) fn rb_enc_associate(obj: VALUE, enc: *mut rb_encoding) -> VALUE;
+    pub(crate) fn rb_enc_associate_index(obj: VALUE, index: ::libc::c_int) -> VALUE;
+    pub(crate) fn rb_enc_from_encoding(enc: *mut rb_encoding) -> VALUE;
+    pub(crate) fn rb_to_encoding(obj: VALUE) -> *mut rb_encoding;
+    pub(crate) fn rb_enc_dummy_p(enc: *mut rb_encoding) -> ::libc::c_int;
+    pub(crate) fn rb_enc_str_coderange(string: VALUE) -> ::libc::c_int;
+    pub(crate) fn rb_enc_str_asciionly_p(string: VALUE) -> ::libc::c_int;
+    pub(crate) fn rb_str_encode(string: VALUE, to: VALUE, ecflags: ::libc::c_int, ecopts: VALUE) -> VALUE;
+    pub(crate) fn rb_intern3(name: *const ::libc::c_char, len: ::libc::c_long, enc: *mut rb_encoding) -> ID;
+    pub(crate) fn rb_utf8_encoding() -> *mut rb_encoding;
+    pub(crate) fn rb_utf8_encindex() -> ::libc::c_int;
+    pub(crate) fn rb_ascii8bit_encoding() -> *mut rb_encoding;
+    pub(crate) fn rb_usascii_encoding() -> *mut rb_encoding;
+}
+
+// ENC_CODERANGE_BROKEN ((int)(FL_USER8|FL_USER9))
+pub(crate) const ENC_CODERANGE_BROKEN: ::libc::c_int = 1 << (12 + 8) | 1 << (12 + 9);
+
+// One of the encodings ruby knows about, e.g. the encoding of a String:
+//
+//     let sjis = Encoding::find("Shift_JIS").unwrap();
+//     let utf8 = string.encode(Encoding::utf8())?;
+//
+// Encodings live as long as the VM, so this is a plain (copyable) pointer.
+#[derive(Clone, Copy)]
+pub struct Encoding {
+    enc: *mut rb_encoding
+}
+
+impl Encoding {
+    // Encoding named `name` or one of its aliases, case insensitive. None for unknown names.
+    pub fn find(name: &str) -> Option<Encoding> {
+        let c_name = CString::new(name).ok()?;
+        let index = unsafe { rb_enc_find_index(c_name.as_ptr()) };
+        if index < 0 { None } else { Encoding::from_index(index) }
+    }
+
+    pub fn utf8() -> Encoding {
+        Encoding { enc: unsafe { rb_utf8_encoding() } }
+    }
+
+    // Binary, a.k.a. ASCII-8BIT
+    pub fn ascii_8bit() -> Encoding {
+        Encoding { enc: unsafe { rb_ascii8bit_encoding() } }
+    }
+
+    pub fn us_ascii() -> Encoding {
+        Encoding { enc: unsafe { rb_usascii_encoding() } }
+    }
+
+    // Encoding of a String, Symbol or Regexp, None for values without one
+    pub fn of(value: VALUE) -> Option<Encoding> {
+        let enc = unsafe { rb_enc_get(value) };
+        if enc.is_null() { None } else { Some(Encoding { enc: enc }) }
+    }
+
+    fn from_index(index: ::libc::c_int) -> Option<Encoding> {
+        let enc = unsafe { rb_enc_from_index(index) };
+        if enc.is_null() { None } else { Some(Encoding { enc: enc }) }
+    }
+
+    pub fn name(&self) -> &'static str {
+        unsafe { CStr::from_ptr((*self.enc).name) }.to_str().unwrap_or("?")
+    }
+
+    // Position in ruby's table of encodings
+    pub fn index(&self) -> i32 {
+        unsafe { rb_enc_to_index(self.enc) }
+    }
+
+    // Dummy encodings (e.g. UTF-16) are known by name but can't be processed character-wise
+    pub fn is_dummy(&self) -> bool {
+        unsafe { rb_enc_dummy_p(self.enc) != 0 }
+    }
+
+    // rb_enc_asciicompat(enc) (rb_enc_mbminlen(enc)==1 && !rb_enc_dummy_p(enc))
+    // ASCII characters are single bytes of the same value, so ASCII text reads the same as in UTF-8
+    pub fn is_ascii_compatible(&self) -> bool {
+        let min_char_len = unsafe { (*self.enc).min_enc_len };
+        min_char_len == 1 && !self.is_dummy()
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut rb_encoding {
+        self.enc
+    }
+}
+
+// Contents of String `value` as UTF-8: read as is when already UTF-8 (or plain ASCII in an
+// ASCII compatible encoding), transcoded otherwise. Broken or untranscodable strings fail.
+pub(crate) fn string_to_utf8(value: VALUE) -> Result<String, ConversionError> {
+    let utf8 = Encoding::utf8();
+    let encoding = Encoding::of(value).unwrap_or(utf8);
+    let ascii_only = encoding.is_ascii_compatible() && unsafe { rb_enc_str_asciionly_p(value) != 0 };
+    let utf8_value = if encoding == utf8 || ascii_only {
+        value
+    } else {
+        protect(|| unsafe { rb_str_encode(value, utf8.to_value(), 0, RUBY_Qnil as VALUE) })
+            .map_err(|exception| ConversionError::bad_encoding(value, exception.message()))?
+    };
+    String::from_utf8(unsafe { string_bytes(utf8_value) }.to_vec())
+        .map_err(|_| ConversionError::bad_encoding(value, format!("invalid byte sequence in {}", encoding.name())))
+}
+
+impl PartialEq for Encoding {
+    fn eq(&self, other: &Encoding) -> bool {
+        self.enc == other.enc
+    }
+}
+
+impl Eq for Encoding {}
+
+impl FromValue for Encoding {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        if RTEST(unsafe { rb_obj_is_kind_of(value, rb_cEncoding) }) {
+            Ok(FromValue::from_value_unchecked(value))
+        } else {
+            Err(ConversionError::new(RubyType::Data, value).expecting_class("Encoding"))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Encoding { enc: unsafe { rb_to_encoding(value) } }
+    }
+}
+
+// The Encoding object
+impl ToValue for Encoding {
+    fn to_value(&self) -> VALUE {
+        unsafe { rb_enc_from_encoding(self.enc) }
+    }
+}
+
+impl fmt::Debug for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Encoding({})", self.name())
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
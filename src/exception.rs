@@ -0,0 +1,140 @@
+use ruby::*;
+use macros::*;
+use super::{funcall, Array, ConversionError, FromValue, ToValue, RubyType};
+use super::bytes::lossy_string;
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+
+// TAG_RAISE from eval_intern.h, the state rb_protect reports for a raised exception.
+// Other states are non-local exits (break, next, throw, ...) travelling up the stack.
+const TAG_RAISE: i32 = 6;
+
+// Errors which can be reported to ruby as an exception, once the rust frames producing them are gone
+pub trait IntoException: Sized {
+    fn into_exception(self) -> VALUE;
+
+    // Raises the error in the ruby VM. Ruby longjmps out of the current frame,
+    // so nothing owning resources should be alive on the stack when calling this.
+    fn raise(self) -> ! {
+        let exception = self.into_exception();
+        unsafe { rb_exc_raise(exception) };
+        unreachable!()
+    }
+}
+
+// Exception raised by ruby code and caught with `protect`.
+// `protect` also stops non-local exits like `break` from a block; those only support `raise`,
+// which resumes them.
+pub struct RubyException {
+    val: VALUE,
+    state: i32
+}
+
+impl RubyException {
+    pub fn new(exception: VALUE) -> Self {
+        RubyException { val: exception, state: TAG_RAISE }
+    }
+
+    // Whatever stopped a `protect` call, `errinfo` is only an exception object for TAG_RAISE
+    pub(crate) fn from_state(errinfo: VALUE, state: i32) -> Self {
+        RubyException { val: errinfo, state: state }
+    }
+
+    // True for break/next/throw and the like, which aren't exceptions
+    pub fn is_jump(&self) -> bool {
+        self.state != TAG_RAISE
+    }
+
+    pub fn class_name(&self) -> String {
+        if self.is_jump() {
+            return String::new();
+        }
+        unsafe { CStr::from_ptr(rb_obj_classname(self.val)) }.to_string_lossy().into_owned()
+    }
+
+    // exception.message, empty if the exception fails to produce one
+    pub fn message(&self) -> String {
+        if self.is_jump() {
+            return String::new();
+        }
+        funcall(self.val, "message", &[]).ok()
+            .filter(|&message| RubyType::from_value(message) == RubyType::String)
+            .map(lossy_string)
+            .unwrap_or_default()
+    }
+
+    // exception.backtrace, empty when the exception was never raised
+    pub fn backtrace(&self) -> Vec<String> {
+        if self.is_jump() {
+            return Vec::new();
+        }
+        match funcall(self.val, "backtrace", &[]).ok().and_then(Array::from_value) {
+            Some(lines) => lines.into_iter()
+                .filter(|&line| RubyType::from_value(line) == RubyType::String)
+                .map(lossy_string)
+                .collect(),
+            None => Vec::new()
+        }
+    }
+
+    // Checks the exception against one of the exception classes, e.g. `exception.is_a(unsafe { rb_eArgError })`
+    pub fn is_a(&self, class: VALUE) -> bool {
+        !self.is_jump() && RTEST(unsafe { rb_obj_is_kind_of(self.val, class) })
+    }
+
+    // Re-raises the exception or resumes the non-local exit, see `IntoException::raise`
+    pub fn raise(self) -> ! {
+        if self.is_jump() {
+            unsafe { rb_jump_tag(self.state) };
+        } else {
+            unsafe { rb_exc_raise(self.val) };
+        }
+        unreachable!()
+    }
+}
+
+impl IntoException for RubyException {
+    fn into_exception(self) -> VALUE {
+        self.val
+    }
+
+    fn raise(self) -> ! {
+        RubyException::raise(self)
+    }
+}
+
+// Lets `?` mix conversion failures with caught exceptions, the error becomes the TypeError it would raise
+impl From<ConversionError> for RubyException {
+    fn from(error: ConversionError) -> Self {
+        RubyException::new(error.to_exception())
+    }
+}
+
+impl ToValue for RubyException {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for RubyException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_jump() {
+            return write!(f, "RubyException(jump tag {})", self.state);
+        }
+        write!(f, "RubyException({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
+
+// Formatted the way ruby reports uncaught exceptions: "message (ClassName)"
+impl fmt::Display for RubyException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.class_name())
+    }
+}
+
+impl Error for RubyException {
+    fn description(&self) -> &str {
+        "ruby exception"
+    }
+}
@@ -1,16 +1,24 @@
-use super::RubyType;
+use super::{protect, RubyType, ConversionError};
 use super::ruby::{self, VALUE};
+use super::macros::*;
+use super::encoding::string_to_utf8;
 use super::Nil;
+use std::convert::TryFrom;
 
 pub trait FromValue: Sized {
     // TODO: Use associated const when available and support access from the trait itself, so we can check type
-    fn from_value(value: VALUE) -> Option<Self>;
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError>;
     fn from_value_unchecked(value: VALUE) -> Self;
+
+    fn from_value(value: VALUE) -> Option<Self> {
+        Self::try_from_value(value).ok()
+    }
 }
 
+// The VALUE as is, no conversion. Since VALUE is u64 this is also what `u64` converts with, see `U64`.
 impl FromValue for VALUE {
-    fn from_value(val: VALUE) -> Option<Self> {
-        Some(val)
+    fn try_from_value(val: VALUE) -> Result<Self, ConversionError> {
+        Ok(val)
     }
 
     fn from_value_unchecked(val: VALUE) -> Self {
@@ -20,10 +28,10 @@ impl FromValue for VALUE {
 
 // Core type from value
 impl FromValue for bool {
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::False | RubyType::True => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::False | RubyType::True => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::True, value))
         }
     }
     fn from_value_unchecked(value: VALUE) -> Self {
@@ -36,57 +44,108 @@ impl FromValue for bool {
 }
 
 
-impl FromValue for i64 {
-    fn from_value_unchecked(value: VALUE) -> Self {
-        unsafe { ruby::rb_fix2int(value) }
+// Integer (Fixnum or Bignum) as a long. Bignums outside of it fail with RangeError rather than wrapping.
+fn integer_to_i64(value: VALUE, target: &'static str) -> Result<i64, ConversionError> {
+    match RubyType::from_value(value) {
+        RubyType::Fixnum => Ok(FIX2LONG(value)),
+        RubyType::Bignum => {
+            protect(|| unsafe { ruby::rb_num2long(value) as VALUE })
+                .map(|long| long as i64)
+                .map_err(|_| ConversionError::out_of_range(value, target))
+        }
+        _ => Err(ConversionError::new(RubyType::Fixnum, value))
     }
-    fn from_value(value: VALUE) -> Option<Self> {
-        match RubyType::from_value(value)  {
-            RubyType::Fixnum => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+}
+
+// Same as `integer_to_i64` for unsigned long long. rb_num2ull accepts (and wraps) negative numbers,
+// so those are rejected before getting there.
+fn integer_to_u64(value: VALUE, target: &'static str) -> Result<u64, ConversionError> {
+    match RubyType::from_value(value) {
+        RubyType::Fixnum if FIX2LONG(value) >= 0 => Ok(FIX2LONG(value) as u64),
+        RubyType::Bignum if FIX2LONG(unsafe { ruby::rb_big_cmp(value, INT2FIX(0)) }) > 0 => {
+            protect(|| unsafe { ruby::rb_num2ull(value) as VALUE })
+                .map(|ull| ull as u64)
+                .map_err(|_| ConversionError::out_of_range(value, target))
         }
+        RubyType::Fixnum | RubyType::Bignum => Err(ConversionError::out_of_range(value, target)),
+        _ => Err(ConversionError::new(RubyType::Fixnum, value))
     }
 }
 
-impl FromValue for i32 {
+// Integers convert to every Rust integer type they fit in, u64 through `U64`
+macro_rules! integer_from_value {
+    ($convert:ident, $($ty:ident),*) => {
+        $(
+            impl FromValue for $ty {
+                // Panics when the value isn't an Integer or doesn't fit
+                fn from_value_unchecked(value: VALUE) -> Self {
+                    Self::try_from_value(value).unwrap_or_else(|error| panic!("{}", error))
+                }
+                fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+                    $convert(value, stringify!($ty)).and_then(|integer| {
+                        $ty::try_from(integer).map_err(|_| ConversionError::out_of_range(value, stringify!($ty)))
+                    })
+                }
+            }
+        )*
+    }
+}
+
+integer_from_value!(integer_to_i64, i8, i16, i32, i64, isize);
+integer_from_value!(integer_to_u64, u8, u16, u32, usize);
+
+// Range checked u64. `VALUE` is a c_ulong, i.e. u64 itself, so `u64::from_value` and `5u64.to_value()`
+// pass the raw VALUE through untouched (the Fixnum 5 is the word 11). Wrap numbers in `U64` instead:
+//
+//     let U64(id) = U64::try_from_value(value)?;
+//     U64(id + 1).to_value()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct U64(pub u64);
+
+impl From<U64> for u64 {
+    fn from(number: U64) -> u64 {
+        number.0
+    }
+}
+
+impl FromValue for U64 {
+    // Panics when the value isn't an Integer or doesn't fit
     fn from_value_unchecked(value: VALUE) -> Self {
-        unsafe { ruby::rb_num2short(value) as i32 }
+        Self::try_from_value(value).unwrap_or_else(|error| panic!("{}", error))
     }
-    fn from_value(value: VALUE) -> Option<Self> {
-        match RubyType::from_value(value)  {
-            RubyType::Fixnum => Some(FromValue::from_value_unchecked(value)),
-            _ => None
-        }
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        integer_to_u64(value, "u64").map(U64)
     }
 }
 
+// Float only, see `Numeric` to accept Integers and Rationals too
 impl FromValue for f64 {
     fn from_value_unchecked(value: VALUE) -> Self {
-        unsafe { ruby::rb_float_value(value) }
+        if RB_FLONUM_P(value) {
+            rb_float_flonum_value(value)
+        } else {
+            unsafe { ruby::rb_float_value(value) }
+        }
     }
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::Float => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::Float => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Float, value))
         }
     }
 }
 
+// Strings in other encodings are transcoded to UTF-8, broken ones fail with EncodingError
 impl FromValue for String {
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::String => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::String => string_to_utf8(value),
+            _ => Err(ConversionError::new(RubyType::String, value))
         }
     }
-    fn from_value_unchecked(mut value: VALUE) -> Self {
-        use std::slice;
-        unsafe {
-            let strlen = ruby::rb_str_strlen(value) as usize;
-            let ptr = ruby::rb_string_value_ptr(&mut value) as *const u8;
-            let buf = slice::from_raw_parts(ptr, strlen);
-            String::from_utf8_lossy(buf).into_owned()
-        }
+    // Panics when the value isn't a String or can't be made UTF-8
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Self::try_from_value(value).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
@@ -94,10 +153,10 @@ impl FromValue for Nil {
     fn from_value_unchecked(_value: VALUE) -> Self {
         Nil
     }
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::Nil => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::Nil => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Nil, value))
         }
     }
 }
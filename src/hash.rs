@@ -1,6 +1,7 @@
 use ruby::*;
 use array::Array;
-use super::{cast_str, FromValue, ToValue, RubyType};
+use super::{Call, FromValue, ToValue, RubyType, ConversionError};
+use super::bytes::lossy_string;
 use std::fmt;
 
 // pub fn rb_hash_foreach(arg1: VALUE,
@@ -52,19 +53,18 @@ impl Hash {
     }
 
     pub fn keys(&self) -> Array {
-        let value : Option<Array> = FromValue::from_value(unsafe { rb_funcall(self.val, rb_intern(cast_str("keys\x00")), 0) } );
-        match value {
-            Some(arr) => arr,
-            _ => panic!("Unexpected result of hash.keys")
+        match self.call::<Array>(id!("keys"), ()) {
+            Ok(arr) => arr,
+            Err(exception) => panic!("Unexpected result of hash.keys: {}", exception)
         }
     }
 }
 
 impl FromValue for Hash {
-    fn from_value(value: VALUE) -> Option<Self> {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
         match RubyType::from_value(value)  {
-            RubyType::Hash => Some(FromValue::from_value_unchecked(value)),
-            _ => None
+            RubyType::Hash => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Hash, value))
         }
     }
     fn from_value_unchecked(value: VALUE) -> Self {
@@ -80,6 +80,6 @@ impl ToValue for Hash {
 
 impl fmt::Debug for Hash {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Hash({})", String::from_value_unchecked(unsafe { rb_inspect(self.val) }) )
+        write!(f, "Hash({})", lossy_string(unsafe { rb_inspect(self.val) }) )
     }
 }
@@ -0,0 +1,90 @@
+use ruby::{self, ID, VALUE};
+use super::encoding::{rb_intern3, rb_utf8_encoding};
+use super::{FromValue, ToValue, ConversionError, Symbol};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// pub fn rb_intern3(name: *const ::libc::c_char, len: ::libc::c_long, enc: *mut rb_encoding) -> ID;
+// pub fn rb_id2str(arg1: ID) -> VALUE;
+
+// Interned method/variable name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id(ID);
+
+impl Id {
+    // Interns `name` as UTF-8, it doesn't need to be NUL terminated. rb_intern/rb_intern2 read names
+    // as US-ASCII and raise EncodingError for anything else.
+    pub fn new(name: &str) -> Self {
+        Id(unsafe { rb_intern3(name.as_ptr() as *const i8, name.len() as i64, rb_utf8_encoding()) })
+    }
+
+    pub fn from_raw(id: ID) -> Self {
+        Id(id)
+    }
+
+    pub fn as_raw(&self) -> ID {
+        self.0
+    }
+
+    // Fails for names which aren't valid in (or convertible to) UTF-8
+    pub fn name(&self) -> Result<String, ConversionError> {
+        String::try_from_value(unsafe { ruby::rb_id2str(self.0) })
+    }
+}
+
+// Converted from and to Symbols, the way ruby passes names around (e.g. `define_method(:name)`)
+impl FromValue for Id {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        Symbol::try_from_value(value).map(|symbol| symbol.id())
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Symbol::from_value_unchecked(value).id()
+    }
+}
+
+impl ToValue for Id {
+    fn to_value(&self) -> VALUE {
+        Symbol::from_id(*self).to_value()
+    }
+}
+
+// Id interned on first use and cached from then on, so it can live in a `static`:
+//
+//     static SIZE: StaticId = StaticId::new("size");
+//     array.call::<i64>(&SIZE, ())
+//
+// or through `id!("size")`, which declares the static in place.
+pub struct StaticId {
+    name: &'static str,
+    // 0 until interned, ruby never hands out 0 as an ID
+    id: AtomicUsize
+}
+
+impl StaticId {
+    pub const fn new(name: &'static str) -> Self {
+        StaticId { name: name, id: AtomicUsize::new(0) }
+    }
+
+    pub fn id(&self) -> Id {
+        match self.id.load(Ordering::Relaxed) {
+            0 => {
+                let id = Id::new(self.name);
+                self.id.store(id.as_raw() as usize, Ordering::Relaxed);
+                id
+            },
+            id => Id::from_raw(id as ID)
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+// Cached `Id` for a literal name: `array.call::<i64>(id!("size"), ())`
+#[macro_export]
+macro_rules! id {
+    ($name:expr) => {{
+        static ID: $crate::StaticId = $crate::StaticId::new($name);
+        ID.id()
+    }}
+}
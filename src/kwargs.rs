@@ -0,0 +1,85 @@
+use ruby::*;
+use super::{protect, FromValue, ToValue, ConversionError, RubyException, Hash, Id};
+
+// pub fn rb_get_kwargs(keyword_hash: VALUE, table: *const ID, required: ::libc::c_int,
+//                      optional: ::libc::c_int, arg1: *mut VALUE) -> ::libc::c_int;
+// pub fn rb_extract_keywords(orighash: *mut VALUE) -> VALUE;
+
+// Keyword arguments checked against the declared names, the way ruby checks them for methods
+// defined in ruby: missing required keywords and unknown keywords raise ArgumentError.
+//
+//     let args = unsafe { Args::scan(argc, argv, "1:") }?;
+//     let kwargs = KwArgs::parse(args.keywords(), &["name"], &["separator"])?;
+//     let name: String = kwargs.required("name")?;
+//     let separator = kwargs.get("separator")?.unwrap_or(",".to_string());
+pub struct KwArgs {
+    names: Vec<String>,
+    values: Vec<VALUE>,
+    rest: Option<Hash>
+}
+
+impl KwArgs {
+    pub fn parse(keywords: Option<Hash>, required: &[&str], optional: &[&str]) -> Result<KwArgs, RubyException> {
+        KwArgs::get_kwargs(keywords, required, optional, false)
+    }
+
+    // Same as `parse`, but unknown keywords are kept (like `**rest`) instead of raising
+    pub fn parse_with_rest(keywords: Option<Hash>, required: &[&str], optional: &[&str]) -> Result<KwArgs, RubyException> {
+        KwArgs::get_kwargs(keywords, required, optional, true)
+    }
+
+    fn get_kwargs(keywords: Option<Hash>, required: &[&str], optional: &[&str], rest: bool) -> Result<KwArgs, RubyException> {
+        let names: Vec<String> = required.iter().chain(optional.iter()).map(|name| name.to_string()).collect();
+        let ids: Vec<ID> = names.iter().map(|name| Id::new(name).as_raw()).collect();
+        let mut values = vec![RUBY_Qundef as VALUE; ids.len()];
+
+        // rb_get_kwargs deletes the keywords it finds, work on a copy of the caller's hash
+        let hash = keywords.map_or(RUBY_Qnil as VALUE, |keywords| unsafe { rb_hash_dup(keywords.to_value()) });
+        // Negative number of optional keywords means the rest is allowed: -1 - optional
+        let optional_count = if rest { -1 - optional.len() as i32 } else { optional.len() as i32 };
+        protect(|| {
+            unsafe { rb_get_kwargs(hash, ids.as_ptr(), required.len() as i32, optional_count, values.as_mut_ptr()) };
+            RUBY_Qnil as VALUE
+        })?;
+
+        let rest = if rest && hash != RUBY_Qnil as VALUE {
+            Some(Hash::from_value_unchecked(hash))
+        } else {
+            None
+        };
+        Ok(KwArgs { names: names, values: values, rest: rest })
+    }
+
+    // Splits a hash into its Symbol keyed part (the keywords) and everything else, as ruby does for
+    // the trailing hash of a method call. Either part is None when empty.
+    pub fn extract(hash: Hash) -> (Option<Hash>, Option<Hash>) {
+        let mut other = hash.to_value();
+        let keywords = unsafe { rb_extract_keywords(&mut other) };
+        let wrap = |value: VALUE| if value == 0 { None } else { Some(Hash::from_value_unchecked(value)) };
+        (wrap(keywords), wrap(other))
+    }
+
+    // Value of a keyword declared as required
+    pub fn required<T>(&self, name: &str) -> Result<T, ConversionError> where T: FromValue {
+        match self.get(name) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => panic!("keyword {} was not declared as required", name),
+            Err(error) => Err(error)
+        }
+    }
+
+    // Value of an optional keyword, None when it wasn't passed
+    pub fn get<T>(&self, name: &str) -> Result<Option<T>, ConversionError> where T: FromValue {
+        let index = self.names.iter().position(|declared| declared == name)
+            .unwrap_or_else(|| panic!("keyword {} was not declared", name));
+        match self.values[index] {
+            value if value == RUBY_Qundef as VALUE => Ok(None),
+            value => T::try_from_value(value).map(Some).map_err(|error| error.at_keyword(name))
+        }
+    }
+
+    // Keywords which weren't declared, only kept by `parse_with_rest`
+    pub fn rest(&self) -> Option<&Hash> {
+        self.rest.as_ref()
+    }
+}
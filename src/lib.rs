@@ -3,23 +3,81 @@ extern crate libc;
 
 #[allow(dead_code, non_upper_case_globals, non_camel_case_types, non_snake_case)]
 mod ruby;
+#[macro_use]
+mod id;
 mod array;
 mod hash;
+mod bignum;
+mod bytes;
+mod rstr;
+mod rstring;
+mod encoding;
+mod numeric;
+mod rational;
+mod complex;
+mod range;
 #[allow(dead_code, non_upper_case_globals, non_camel_case_types, non_snake_case)]
 mod macros;
+#[macro_use]
+mod panic_guard;
 mod from_value;
 mod to_value;
 mod ruby_type;
+mod conversion_error;
+mod exception;
+mod protect;
+#[macro_use]
+mod mark;
+mod class;
+mod define_methods;
+#[macro_use]
+mod typed_method;
+#[macro_use]
+mod typed_data;
+mod args;
+mod kwargs;
+mod block;
+mod proc;
+mod call;
+#[macro_use]
+mod symbol;
 
 use ruby::*;
 use macros::*;
 pub use array::Array;
 pub use hash::Hash;
-pub use from_value::FromValue;
+pub use bignum::{Bignum, WordOrder, Word};
+pub use bytes::Bytes;
+pub use rstr::RStr;
+pub use rstring::RString;
+pub use encoding::Encoding;
+pub use numeric::Numeric;
+pub use rational::Rational;
+pub use complex::Complex;
+pub use range::Range;
+pub use from_value::{FromValue, U64};
 pub use to_value::ToValue;
 pub use ruby_type::RubyType;
+pub use conversion_error::ConversionError;
+pub use panic_guard::{catch_panic, catch_errors, rust_panic_class};
+pub use exception::{RubyException, IntoException};
+pub use protect::{protect, funcall, eval, require};
+pub use typed_data::{TypedData, DataType, RubyDataType};
+pub use mark::Mark;
+pub use class::{Class, Module};
+pub use define_methods::RubyCallable;
+pub use typed_method::{convert_argument, MethodReturn};
+pub use args::{Args, ArgSpec};
+pub use kwargs::KwArgs;
+pub use block::{call_with_block, block_given, yield_value, yield_values, yield_splat, block_proc,
+                enumerator, sized_enumerator, EnumeratorSizeFn};
+pub use proc::Proc;
+pub use id::{Id, StaticId};
+pub use call::{Call, IntoId, Arguments};
+pub use symbol::Symbol;
+pub use ruby::VALUE;
 
-use std::ffi::{CString, CStr};
+use std::ffi::CStr;
 use std::fmt;
 
 pub struct InspectValue(VALUE);
@@ -32,11 +90,11 @@ impl fmt::Debug for InspectValue {
             RubyType::Fixnum => write!(f, "Fixnum({})", i64::from_value_unchecked(self.0)),
             RubyType::Float => write!(f, "Float({})", f64::from_value_unchecked(self.0)),
             RubyType::True | RubyType::False => write!(f, "Bool({})", bool::from_value_unchecked(self.0)),
-            RubyType::String => write!(f, "String({})", String::from_value_unchecked(self.0)),
-            // RubyType::Symbol => write!(f, "Symbol(_not_implemented_yet_)"),
+            RubyType::String => write!(f, "String({})", bytes::lossy_string(self.0)),
+            RubyType::Symbol => write!(f, "{:?}", Symbol::from_value_unchecked(self.0)),
             RubyType::Array => write!(f, "{:?}", Array::from_value_unchecked(self.0)),
             RubyType::Hash => write!(f, "{:?}", Hash::from_value_unchecked(self.0)),
-            _ => write!(f, "Object({})", String::from_value_unchecked(unsafe { rb_inspect(self.0) }) )
+            _ => write!(f, "Object({})", bytes::lossy_string(unsafe { rb_inspect(self.0) }) )
         }
     }
 }
@@ -48,50 +106,36 @@ pub struct Nil;
 #[no_mangle]
 #[allow(non_snake_case)]
 pub unsafe extern "C" fn Init_test_rust() {
-  let my_mod = rb_define_module(cast_str("TestRust\x00"));
-  ruby_define_singleton_method(my_mod, "foo", foo, 1);
+  let my_mod = Module::define("TestRust").build();
+  (foo as extern "C" fn(VALUE, VALUE) -> VALUE).define_singleton_method(my_mod, "foo");
 }
 
-#[no_mangle]
-pub extern "C" fn foo(_this: VALUE, arg: VALUE) -> VALUE { //argc: usize, argv: *const VALUE, this: VALUE
-    ruby_eval_string("puts 'hello world from rust'");
-    let mut ary = Array::new();
-    ary.push(Nil);
-    ary.push(true);
-    ary.push(25);
-    ary.push(rb_type(arg) as i64);
-    println!("Type of arg: {:?}", RubyType::from_value(arg));
-    // println!("Arg value: {:?}", RubyValue::from_value(arg));
-    if let Some(arr) = Array::from_value(arg) {
-        for val in arr {
-            println!("Array item: {:?}", InspectValue(val))
+ruby_fn! {
+    #[no_mangle]
+    pub fn foo(_this: VALUE, arg: VALUE) -> VALUE { //argc: usize, argv: *const VALUE, this: VALUE
+        eval("puts 'hello world from rust'").unwrap();
+        let mut ary = Array::new();
+        ary.push(Nil);
+        ary.push(true);
+        ary.push(25);
+        ary.push(rb_type(arg) as i64);
+        println!("Type of arg: {:?}", RubyType::from_value(arg));
+        // println!("Arg value: {:?}", RubyValue::from_value(arg));
+        if let Some(arr) = Array::from_value(arg) {
+            for val in arr {
+                println!("Array item: {:?}", InspectValue(val))
+            }
         }
-    }
-
-    if let Some(hash) = Hash::from_value(arg) {
-        println!("Hash len: {:?}", i32::from_value(hash.len()));
-        println!("Hash keys: {:?}", hash.keys().into_iter().map(|itm| InspectValue(itm)).collect::<Vec<_>>() );
-    }
-
-    println!("Arg class name: {:?}", unsafe { CStr::from_ptr(rb_obj_classname(arg)) } );
-    println!("Arg class name manual: {:?}", unsafe { String::from_value(rb_class_name(rb_funcall(arg, rb_intern(cast_str("class\x00")), 0))) } );
-    ary.to_value()
-}
 
-fn ruby_define_singleton_method(module: VALUE, name: &str, func: extern "C" fn(VALUE, VALUE) -> VALUE, argc: i32) {
-    use std::mem::transmute;
-    let buf = CString::new(name).unwrap();
-    unsafe { rb_define_singleton_method(module, buf.as_ptr() as *const i8, Some(transmute(func)), argc); }
-}
-
-fn ruby_eval_string(program: &str) {
-    let buf = CString::new(program).unwrap();
-    unsafe { rb_eval_string(buf.as_ptr() as *const i8) };
-}
+        if let Some(hash) = Hash::from_value(arg) {
+            println!("Hash len: {:?}", i32::from_value(hash.len()));
+            println!("Hash keys: {:?}", hash.keys().into_iter().map(|itm| InspectValue(itm)).collect::<Vec<_>>() );
+        }
 
-#[inline(always)]
-fn cast_str(string: &'static str) -> *const i8 {
-    string.as_ptr() as *const i8
+        println!("Arg class name: {:?}", unsafe { CStr::from_ptr(rb_obj_classname(arg)) } );
+        println!("Arg class name manual: {:?}", funcall(arg, "class", &[]).map(|class| String::from_value(unsafe { rb_class_name(class) })) );
+        ary.to_value()
+    }
 }
 
 #[inline(always)]
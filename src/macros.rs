@@ -5,6 +5,12 @@ pub fn INT2FIX(i: i64) -> VALUE {
     (i << 1 | RUBY_FIXNUM_FLAG as i64) as VALUE
 }
 
+// RB_FIX2LONG(x) ((long)(((SIGNED_VALUE)(x))>>(int)1))
+#[inline(always)]
+pub fn FIX2LONG(x: VALUE) -> i64 {
+    (x as i64) >> 1
+}
+
 // RB_IMMEDIATE_P(x) ((VALUE)(x) & RUBY_IMMEDIATE_MASK)
 pub fn RB_IMMEDIATE_P(x: VALUE) -> bool {
     (x as u32 & RUBY_IMMEDIATE_MASK) > 0
@@ -20,6 +26,34 @@ pub fn RB_FLONUM_P(x: VALUE) -> bool {
     (x as u32 & RUBY_FLONUM_MASK == RUBY_FLONUM_FLAG)
 }
 
+// rb_float_flonum_value(v): flonums keep the 62 high bits of the double, with the top two exponent
+// bits (always 01 or 10 for doubles in flonum range) folded into b63 and the tag bits moved out
+pub fn rb_float_flonum_value(v: VALUE) -> f64 {
+    // +0.0 has its own representation
+    if v == 0x8000000000000002 {
+        return 0.0;
+    }
+    let b63 = v >> 63;
+    // e: xx1... -> 011..., xx0... -> 100...
+    f64::from_bits(((2 - b63) | (v & !0x03)).rotate_right(3))
+}
+
+// Flonum half of rb_float_new_inline(d): None when `d` needs a heap Float, which is the case
+// for exponents outside of 2**-255 ... 2**257, NaN, the infinities and -0.0
+pub fn rb_float_flonum(d: f64) -> Option<VALUE> {
+    let v = d.to_bits();
+    // b62..b60 of the double
+    let bits = (v >> 60) & 0x7;
+    // 1.72723e-77 would encode as +0.0
+    if v != 0x3000000000000000 && (bits.wrapping_sub(3) & !0x01) == 0 {
+        Some((v.rotate_left(3) & !0x01) | 0x02)
+    } else if v == 0 {
+        Some(0x8000000000000002)
+    } else {
+        None
+    }
+}
+
 // RB_STATIC_SYM_P(x) (((VALUE)(x)&~((~(VALUE)0)<<RUBY_SPECIAL_SHIFT)) == RUBY_SYMBOL_FLAG)
 pub fn RB_STATIC_SYM_P(x: VALUE) -> bool {
     ((x as u32 & (!((!0)<<RUBY_SPECIAL_SHIFT))) == RUBY_SYMBOL_FLAG)
@@ -39,3 +73,112 @@ pub fn NIL_P(v: VALUE) -> bool {
 pub fn RB_BUILTIN_TYPE(x: VALUE) -> u64 {
     unsafe { (*(x as *const Struct_RBasic)).flags as u64 & RUBY_T_MASK as u64 }
 }
+
+// RB_SPECIAL_CONST_P(x) (RB_IMMEDIATE_P(x) || !RTEST(x))
+pub fn RB_SPECIAL_CONST_P(x: VALUE) -> bool {
+    RB_IMMEDIATE_P(x) || !RTEST(x)
+}
+
+// RB_DYNAMIC_SYM_P(x) (!RB_SPECIAL_CONST_P(x) && RB_BUILTIN_TYPE(x) == (RUBY_T_SYMBOL))
+pub fn RB_DYNAMIC_SYM_P(x: VALUE) -> bool {
+    !RB_SPECIAL_CONST_P(x) && RB_BUILTIN_TYPE(x) == RUBY_T_SYMBOL as u64
+}
+
+// RB_SYMBOL_P(x) (RB_STATIC_SYM_P(x)||RB_DYNAMIC_SYM_P(x))
+pub fn RB_SYMBOL_P(x: VALUE) -> bool {
+    RB_STATIC_SYM_P(x) || RB_DYNAMIC_SYM_P(x)
+}
+
+// RUBY_FL_USHIFT and the RString flags built on it, missing from the bindings
+const RUBY_FL_USHIFT: VALUE = 12;
+// RSTRING_NOEMBED RUBY_FL_USER1
+const RSTRING_NOEMBED: VALUE = 1 << (RUBY_FL_USHIFT + 1);
+// RSTRING_EMBED_LEN_SHIFT (RUBY_FL_USHIFT+2)
+const RSTRING_EMBED_LEN_SHIFT: VALUE = RUBY_FL_USHIFT + 2;
+// RSTRING_EMBED_LEN_MASK (RUBY_FL_USER2|RUBY_FL_USER3|RUBY_FL_USER4|RUBY_FL_USER5|RUBY_FL_USER6)
+const RSTRING_EMBED_LEN_MASK: VALUE = 0x1f << RSTRING_EMBED_LEN_SHIFT;
+
+// RSTRING_LEN(str) (!(RBASIC(str)->flags & RSTRING_NOEMBED) ?
+//     (long)((RBASIC(str)->flags >> RSTRING_EMBED_LEN_SHIFT) & (RSTRING_EMBED_LEN_MASK >> RSTRING_EMBED_LEN_SHIFT)) :
+//     RSTRING(str)->as.heap.len)
+pub fn RSTRING_LEN(s: VALUE) -> i64 {
+    unsafe {
+        let string = &mut *(s as *mut Struct_RString);
+        if string.basic.flags & RSTRING_NOEMBED == 0 {
+            ((string.basic.flags & RSTRING_EMBED_LEN_MASK) >> RSTRING_EMBED_LEN_SHIFT) as i64
+        } else {
+            (*string._as.heap()).len
+        }
+    }
+}
+
+// RSTRING_PTR(str) (!(RBASIC(str)->flags & RSTRING_NOEMBED) ? RSTRING(str)->as.ary : RSTRING(str)->as.heap.ptr)
+pub fn RSTRING_PTR(s: VALUE) -> *const u8 {
+    unsafe {
+        let string = &mut *(s as *mut Struct_RString);
+        if string.basic.flags & RSTRING_NOEMBED == 0 {
+            string._as.ary() as *const u8
+        } else {
+            (*string._as.heap()).ptr as *const u8
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64;
+
+    fn round_trip(d: f64) -> Option<f64> {
+        rb_float_flonum(d).map(|v| {
+            assert!(RB_FLONUM_P(v), "{} encoded without the flonum tag", d);
+            rb_float_flonum_value(v)
+        })
+    }
+
+    #[test]
+    fn flonums_round_trip_bit_for_bit() {
+        for &d in &[0.0, 1.0, -1.0, 0.1, -2.5, 3.141592653589793, 1e-70, 1e70, f64::EPSILON, 123456789.123456789] {
+            assert_eq!(round_trip(d).map(f64::to_bits), Some(d.to_bits()));
+        }
+    }
+
+    #[test]
+    fn zeros() {
+        assert_eq!(rb_float_flonum(0.0), Some(0x8000000000000002));
+        assert_eq!(rb_float_flonum_value(0x8000000000000002), 0.0);
+        // the sign wouldn't survive
+        assert_eq!(rb_float_flonum(-0.0), None);
+    }
+
+    #[test]
+    fn exponent_boundaries() {
+        let smallest = 2f64.powi(-255);
+        let above_smallest = f64::from_bits(smallest.to_bits() + 1);
+        let largest = f64::from_bits(2f64.powi(257).to_bits() - 1);
+        // 2**-255 is 0x3000000000000000, which collides with +0.0
+        assert_eq!(smallest.to_bits(), 0x3000000000000000);
+        assert_eq!(rb_float_flonum(smallest), None);
+        assert_eq!(round_trip(above_smallest), Some(above_smallest));
+        assert_eq!(round_trip(-smallest), Some(-smallest));
+        assert_eq!(round_trip(largest), Some(largest));
+        assert_eq!(round_trip(-largest), Some(-largest));
+        assert_eq!(round_trip(2f64.powi(256)), Some(2f64.powi(256)));
+        assert_eq!(rb_float_flonum(2f64.powi(257)), None);
+        assert_eq!(rb_float_flonum(2f64.powi(-256)), None);
+    }
+
+    #[test]
+    fn large_and_small_exponents_are_heap_floats() {
+        for &d in &[1e300, -1e300, 1e-300, -1e-300, f64::MAX, f64::MIN_POSITIVE, 5e-324] {
+            assert_eq!(rb_float_flonum(d), None, "{} should be a heap Float", d);
+        }
+    }
+
+    #[test]
+    fn nan_and_infinities_are_heap_floats() {
+        assert_eq!(rb_float_flonum(f64::NAN), None);
+        assert_eq!(rb_float_flonum(f64::INFINITY), None);
+        assert_eq!(rb_float_flonum(f64::NEG_INFINITY), None);
+    }
+}
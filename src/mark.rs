@@ -0,0 +1,187 @@
+use ruby::*;
+use super::{Array, Hash, Bignum, Bytes, RString, Numeric, Rational, Complex, Range, U64, Nil, RubyException, Proc, Symbol, ToValue};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash as StdHash, BuildHasher};
+
+// pub fn rb_gc_mark(arg1: VALUE) -> ();
+// pub fn rb_gc_mark_maybe(arg1: VALUE) -> ();
+
+// Tells the ruby GC about every VALUE reachable from a rust value.
+// Wrapped rust structs holding ruby objects have to implement it (usually through `derive_mark!`)
+// and be declared with `typed_data!(Type, "Name", mark)`, otherwise the objects they hold
+// can be collected while still referenced from rust.
+pub trait Mark {
+    fn mark(&self);
+}
+
+// A raw VALUE might be anything (Qundef, an immediate, a stale object), let ruby check it
+impl Mark for VALUE {
+    fn mark(&self) {
+        unsafe { rb_gc_mark_maybe(*self) }
+    }
+}
+
+impl Mark for Array {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Hash {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Bignum {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for RString {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Rational {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Complex {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Range {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for Proc {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+// Dynamic symbols (String#to_sym) are collectable, static ones are ignored by rb_gc_mark
+impl Mark for Symbol {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl Mark for RubyException {
+    fn mark(&self) {
+        unsafe { rb_gc_mark(self.to_value()) }
+    }
+}
+
+impl<T> Mark for Option<T> where T: Mark {
+    fn mark(&self) {
+        if let Some(ref value) = *self {
+            value.mark()
+        }
+    }
+}
+
+impl<T> Mark for Box<T> where T: Mark {
+    fn mark(&self) {
+        (**self).mark()
+    }
+}
+
+impl<T> Mark for [T] where T: Mark {
+    fn mark(&self) {
+        for value in self {
+            value.mark()
+        }
+    }
+}
+
+impl<T> Mark for Vec<T> where T: Mark {
+    fn mark(&self) {
+        self[..].mark()
+    }
+}
+
+impl<T> Mark for VecDeque<T> where T: Mark {
+    fn mark(&self) {
+        for value in self {
+            value.mark()
+        }
+    }
+}
+
+impl<K, V, S> Mark for HashMap<K, V, S> where K: Mark + Eq + StdHash, V: Mark, S: BuildHasher {
+    fn mark(&self) {
+        for (key, value) in self {
+            key.mark();
+            value.mark();
+        }
+    }
+}
+
+impl<K, V> Mark for BTreeMap<K, V> where K: Mark, V: Mark {
+    fn mark(&self) {
+        for (key, value) in self {
+            key.mark();
+            value.mark();
+        }
+    }
+}
+
+impl<A, B> Mark for (A, B) where A: Mark, B: Mark {
+    fn mark(&self) {
+        self.0.mark();
+        self.1.mark();
+    }
+}
+
+// Plain rust data doesn't reference ruby objects, so derived impls can skip over it.
+// u64 is left out: it's the same type as VALUE and gets marked conservatively.
+macro_rules! mark_nothing {
+    ($($ty:ty),*) => {
+        $(
+            impl Mark for $ty {
+                fn mark(&self) {}
+            }
+        )*
+    }
+}
+
+mark_nothing!(Nil, (), bool, char, i8, i16, i32, i64, isize, u8, u16, u32, usize, f32, f64, String, Bytes, Numeric, U64);
+
+// Wraps a struct definition and implements `Mark` by marking every field
+//
+//     derive_mark! {
+//         pub struct Graph {
+//             nodes: Array,
+//             names: HashMap<String, VALUE>,
+//         }
+//     }
+#[macro_export]
+macro_rules! derive_mark {
+    ($(#[$attr:meta])* pub struct $name:ident { $($(#[$field_attr:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)* }) => {
+        $(#[$attr])*
+        pub struct $name { $($(#[$field_attr])* $field_vis $field: $ty),* }
+        $crate::derive_mark!(@impl $name { $($field),* });
+    };
+    ($(#[$attr:meta])* struct $name:ident { $($(#[$field_attr:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)* }) => {
+        $(#[$attr])*
+        struct $name { $($(#[$field_attr])* $field_vis $field: $ty),* }
+        $crate::derive_mark!(@impl $name { $($field),* });
+    };
+    (@impl $name:ident { $($field:ident),* }) => {
+        impl $crate::Mark for $name {
+            fn mark(&self) {
+                $($crate::Mark::mark(&self.$field);)*
+            }
+        }
+    };
+}
@@ -0,0 +1,53 @@
+use ruby::*;
+use macros::*;
+use super::{protect, ToValue, FromValue, RubyType, ConversionError};
+
+// pub fn rb_num2dbl(arg1: VALUE) -> ::libc::c_double;
+// pub fn rb_to_float(arg1: VALUE) -> VALUE;
+
+// Any Numeric as a float, the way ruby's own float arguments (e.g. Math.sqrt) accept them:
+// Integers and Rationals are converted, other Numerics go through #to_f.
+//
+//     ruby_method! {
+//         fn scale(this: VALUE, factor: Numeric) -> f64 {
+//             factor.0 * 2.0
+//         }
+//     }
+//
+// Strict `f64` only takes Floats.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Numeric(pub f64);
+
+impl From<Numeric> for f64 {
+    fn from(numeric: Numeric) -> f64 {
+        numeric.0
+    }
+}
+
+impl FromValue for Numeric {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value) {
+            RubyType::Float => Ok(Numeric(f64::from_value_unchecked(value))),
+            RubyType::Fixnum => Ok(Numeric(FIX2LONG(value) as f64)),
+            // Bignums beyond f64 become infinite (with a warning)
+            RubyType::Bignum | RubyType::Rational => Ok(Numeric(unsafe { rb_num2dbl(value) })),
+            _ if RTEST(unsafe { rb_obj_is_kind_of(value, rb_cNumeric) }) => {
+                // e.g. a Complex with an imaginary part raises RangeError
+                protect(|| unsafe { rb_to_float(value) })
+                    .map(|float| Numeric(f64::from_value_unchecked(float)))
+                    .map_err(|_| ConversionError::new(RubyType::Float, value))
+            }
+            _ => Err(ConversionError::new(RubyType::Float, value).expecting_class("Numeric"))
+        }
+    }
+    // Panics when the value isn't convertible to a float
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Self::try_from_value(value).unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl ToValue for Numeric {
+    fn to_value(&self) -> VALUE {
+        self.0.to_value()
+    }
+}
@@ -0,0 +1,70 @@
+use ruby::*;
+use super::{Class, ToValue, IntoException};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+// Unwinding across an `extern "C"` boundary aborts the ruby process, so every function
+// the VM calls into has to stop panics before they reach it.
+// `catch_panic` runs the body, and if it panicked converts the payload into a `RustPanic`
+// exception (subclass of RuntimeError). The exception is raised from `catch_panic` itself,
+// at which point the closure and everything it owned is already unwound and dropped.
+pub fn catch_panic<F>(func: F) -> VALUE where F: FnOnce() -> VALUE {
+    let exception = match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => return value,
+        Err(payload) => panic_exception(payload)
+    };
+    unsafe { rb_exc_raise(exception) };
+    unreachable!()
+}
+
+// Same as `catch_panic` for bodies which can fail, the error is raised as an exception
+// once the body has returned.
+pub fn catch_errors<F, E>(func: F) -> VALUE where F: FnOnce() -> Result<VALUE, E>, E: IntoException {
+    let exception = match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error.raise(),
+        Err(payload) => panic_exception(payload)
+    };
+    unsafe { rb_exc_raise(exception) };
+    unreachable!()
+}
+
+// RustPanic < RuntimeError, defined on first use.
+// rb_define_class returns the already defined class on subsequent calls.
+pub fn rust_panic_class() -> VALUE {
+    Class::define("RustPanic").superclass(unsafe { rb_eRuntimeError }).build()
+}
+
+fn panic_exception(payload: Box<dyn Any + Send>) -> VALUE {
+    let message = if let Some(message) = payload.downcast_ref::<&'static str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Rust panic".to_string()
+    };
+    unsafe { rb_exc_new_str(rust_panic_class(), message.to_value()) }
+}
+
+// Defines an `extern "C"` function which can be handed to the VM, with the body guarded by `catch_panic`.
+//
+//     ruby_fn! {
+//         pub fn foo(this: VALUE, arg: VALUE) -> VALUE {
+//             ...
+//         }
+//     }
+#[macro_export]
+macro_rules! ruby_fn {
+    ($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),*) -> VALUE $body:block) => {
+        $(#[$attr])*
+        pub extern "C" fn $name($($arg: $ty),*) -> VALUE {
+            $crate::catch_panic(move || $body)
+        }
+    };
+    ($(#[$attr:meta])* fn $name:ident($($arg:ident: $ty:ty),*) -> VALUE $body:block) => {
+        $(#[$attr])*
+        extern "C" fn $name($($arg: $ty),*) -> VALUE {
+            $crate::catch_panic(move || $body)
+        }
+    };
+}
@@ -0,0 +1,127 @@
+use ruby::*;
+use macros::*;
+use super::{protect, catch_panic, ToValue, FromValue, ConversionError, RubyException, RubyType, TypedData, Mark};
+use std::mem::transmute;
+use std::slice;
+
+// pub fn rb_proc_new(arg1: ::std::option::Option<extern "C" fn() -> VALUE>, arg2: VALUE) -> VALUE;
+// pub fn rb_obj_is_proc(arg1: VALUE) -> VALUE;
+// pub fn rb_proc_call(arg1: VALUE, arg2: VALUE) -> VALUE;
+// pub fn rb_proc_call_with_block(arg1: VALUE, argc: ::libc::c_int, argv: *const VALUE, arg2: VALUE) -> VALUE;
+// pub fn rb_proc_arity(arg1: VALUE) -> ::libc::c_int;
+// pub fn rb_proc_lambda_p(arg1: VALUE) -> VALUE;
+
+// Closure along with the state it gets passed, which is marked for the GC
+trait Callback {
+    fn call(&self, args: &[VALUE]) -> VALUE;
+    fn mark(&self);
+}
+
+struct StatefulClosure<S, F> {
+    state: S,
+    func: F
+}
+
+impl<S, F> Callback for StatefulClosure<S, F> where S: Mark, F: Fn(&S, &[VALUE]) -> VALUE {
+    fn call(&self, args: &[VALUE]) -> VALUE {
+        (self.func)(&self.state, args)
+    }
+    fn mark(&self) {
+        self.state.mark()
+    }
+}
+
+// Rust closure behind a Proc, owned by a ruby object so it's dropped when the Proc is collected
+struct RustClosure {
+    callback: Box<dyn Callback>
+}
+
+impl Mark for RustClosure {
+    fn mark(&self) {
+        self.callback.mark()
+    }
+}
+
+typed_data!(RustClosure, "RustClosure", mark);
+
+unsafe extern "C" fn rust_closure_trampoline(_yielded_arg: VALUE, callback_arg: VALUE, argc: ::libc::c_int,
+                                             argv: *const VALUE, _blockarg: VALUE) -> VALUE {
+    let args = if argc > 0 { slice::from_raw_parts(argv, argc as usize) } else { &[] };
+    catch_panic(|| {
+        // Shared, the Proc may be called again from inside the closure
+        let closure = TypedData::<RustClosure>::get_ref_unchecked(callback_arg);
+        closure.callback.call(args)
+    })
+}
+
+pub struct Proc {
+    val: VALUE
+}
+
+impl Proc {
+    // Proc calling a rust closure with the arguments it's called with:
+    //
+    //     let counter = Proc::from_closure(move |args: &[VALUE]| (args.len() as i64).to_value());
+    //
+    // The closure lives as long as the Proc. Panics inside it are raised as RustPanic.
+    //
+    // The GC doesn't see what the closure captures: a captured VALUE (or Array, RString, ...) can be
+    // collected while the Proc is still alive. Pass ruby objects through `from_closure_with` instead.
+    pub fn from_closure<F>(func: F) -> Proc where F: Fn(&[VALUE]) -> VALUE + 'static {
+        Proc::from_closure_with((), move |_, args| func(args))
+    }
+
+    // Same as `from_closure`, with `state` marked for as long as the Proc lives and passed to every call:
+    //
+    //     let log = Array::new();
+    //     let logger = Proc::from_closure_with(log, |log: &Array, args: &[VALUE]| { ... });
+    pub fn from_closure_with<S, F>(state: S, func: F) -> Proc
+        where S: Mark + 'static, F: Fn(&S, &[VALUE]) -> VALUE + 'static
+    {
+        let callback = StatefulClosure { state: state, func: func };
+        // Hidden (class 0), so ObjectSpace never hands it out. The Proc's ifunc keeps it alive as its data.
+        let owner = TypedData::wrap(0, RustClosure { callback: Box::new(callback) });
+        unsafe {
+            let trampoline: rb_block_call_func = rust_closure_trampoline;
+            Proc { val: rb_proc_new(Some(transmute(trampoline)), owner) }
+        }
+    }
+
+    // Number of arguments the Proc takes, negative when some are optional (see Proc#arity)
+    pub fn arity(&self) -> i32 {
+        unsafe { rb_proc_arity(self.val) }
+    }
+
+    pub fn is_lambda(&self) -> bool {
+        RTEST(unsafe { rb_proc_lambda_p(self.val) })
+    }
+
+    pub fn call(&self, args: &[VALUE]) -> Result<VALUE, RubyException> {
+        let args = unsafe { rb_ary_new_from_values(args.len() as i64, args.as_ptr()) };
+        protect(|| unsafe { rb_proc_call(self.val, args) })
+    }
+
+    // Calls the Proc passing `block` (a Proc or nil) as its block
+    pub fn call_with_block(&self, args: &[VALUE], block: VALUE) -> Result<VALUE, RubyException> {
+        protect(|| unsafe { rb_proc_call_with_block(self.val, args.len() as i32, args.as_ptr(), block) })
+    }
+}
+
+impl FromValue for Proc {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        if RTEST(unsafe { rb_obj_is_proc(value) }) {
+            Ok(FromValue::from_value_unchecked(value))
+        } else {
+            Err(ConversionError::new(RubyType::Data, value).expecting_class("Proc"))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Proc { val: value }
+    }
+}
+
+impl ToValue for Proc {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
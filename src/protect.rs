@@ -0,0 +1,67 @@
+use ruby::*;
+use super::{RubyException, Id};
+use std::any::Any;
+use std::ffi::CString;
+use std::panic::{self, AssertUnwindSafe};
+
+// pub fn rb_protect(arg1: ::std::option::Option<extern "C" fn(arg1: VALUE) -> VALUE>,
+//                   arg2: VALUE, arg3: *mut ::libc::c_int) -> VALUE;
+// pub fn rb_errinfo() -> VALUE;
+// pub fn rb_set_errinfo(arg1: VALUE) -> ();
+
+struct ProtectedCall<F> {
+    func: Option<F>,
+    panic: Option<Box<dyn Any + Send>>
+}
+
+extern "C" fn protected_call_trampoline<F>(data: VALUE) -> VALUE where F: FnOnce() -> VALUE {
+    let call = unsafe { &mut *(data as *mut ProtectedCall<F>) };
+    let func = call.func.take().unwrap();
+    // Don't let a panic unwind through rb_protect's C frames, resume it once we're back in rust
+    match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => value,
+        Err(payload) => {
+            call.panic = Some(payload);
+            RUBY_Qnil as VALUE
+        }
+    }
+}
+
+// Runs `func`, catching any ruby exception raised inside it instead of letting ruby
+// longjmp through the rust frames which called into the VM.
+pub fn protect<F>(func: F) -> Result<VALUE, RubyException> where F: FnOnce() -> VALUE {
+    let mut call = ProtectedCall { func: Some(func), panic: None };
+    let mut state = 0;
+    let value = unsafe {
+        rb_protect(Some(protected_call_trampoline::<F>), &mut call as *mut ProtectedCall<F> as VALUE, &mut state)
+    };
+    if let Some(payload) = call.panic {
+        panic::resume_unwind(payload);
+    }
+    if state == 0 {
+        return Ok(value);
+    }
+    let errinfo = unsafe { rb_errinfo() };
+    let exception = RubyException::from_state(errinfo, state);
+    // errinfo of a non-local exit is needed to resume it with rb_jump_tag, leave it in place
+    if !exception.is_jump() {
+        unsafe { rb_set_errinfo(RUBY_Qnil as VALUE) };
+    }
+    Err(exception)
+}
+
+// Calls method `name` on `recv`
+pub fn funcall(recv: VALUE, name: &str, args: &[VALUE]) -> Result<VALUE, RubyException> {
+    let id = Id::new(name).as_raw();
+    protect(|| unsafe { rb_funcallv(recv, id, args.len() as i32, args.as_ptr()) })
+}
+
+pub fn eval(program: &str) -> Result<VALUE, RubyException> {
+    let c_program = CString::new(program).unwrap();
+    protect(|| unsafe { rb_eval_string(c_program.as_ptr()) })
+}
+
+pub fn require(feature: &str) -> Result<VALUE, RubyException> {
+    let c_feature = CString::new(feature).unwrap();
+    protect(|| unsafe { rb_require(c_feature.as_ptr()) })
+}
@@ -0,0 +1,159 @@
+use ruby::*;
+use macros::*;
+use super::{protect, ToValue, FromValue, RubyType, ConversionError, RubyException};
+use super::bytes::lossy_string;
+use std::convert::TryFrom;
+use std::f64;
+use std::fmt;
+use std::ops;
+
+// pub fn rb_range_new(arg1: VALUE, arg2: VALUE, arg3: ::libc::c_int) -> VALUE;
+// pub fn rb_range_beg_len(arg1: VALUE, arg2: *mut ::libc::c_long, arg3: *mut ::libc::c_long,
+//                         arg4: ::libc::c_long, arg5: ::libc::c_int) -> VALUE;
+// pub fn rb_range_values(range: VALUE, begp: *mut VALUE, endp: *mut VALUE, exclp: *mut ::libc::c_int) -> ::libc::c_int;
+
+// Ruby Range. Converts from and to rust's integer ranges, `start..` being `start..Float::INFINITY`:
+//
+//     let range = Range::try_from_value(value)?;
+//     let indices = ops::Range::<i64>::try_from(range)?;
+//     let tail = array.slice(2..)?;
+pub struct Range {
+    val: VALUE
+}
+
+impl Range {
+    // start..end, or start...end when `exclusive`. Raises ArgumentError when start and end can't be compared.
+    pub fn new<S, E>(start: S, end: E, exclusive: bool) -> Result<Range, RubyException> where S: ToValue, E: ToValue {
+        let (start, end) = (start.to_value(), end.to_value());
+        protect(|| unsafe { rb_range_new(start, end, exclusive as i32) }).map(Range::from_value_unchecked)
+    }
+
+    pub fn start<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(self.values().0)
+    }
+
+    pub fn end<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(self.values().1)
+    }
+
+    // Whether the end is excluded (start...end)
+    pub fn is_exclusive(&self) -> bool {
+        self.values().2
+    }
+
+    fn values(&self) -> (VALUE, VALUE, bool) {
+        let (mut start, mut end, mut exclusive) = (RUBY_Qnil as VALUE, RUBY_Qnil as VALUE, 0);
+        unsafe { rb_range_values(self.val, &mut start, &mut end, &mut exclusive) };
+        (start, end, exclusive != 0)
+    }
+
+    // Start and length of the part of a `len` long collection the range selects, the way Array#[]
+    // reads it: negative ends count from the end and the end is clamped to `len`. None when the
+    // start lies outside of the collection, TypeError for non Integer ends.
+    pub fn start_len(&self, len: usize) -> Result<Option<(usize, usize)>, RubyException> {
+        let (start, end, _) = self.values();
+        // start..Float::INFINITY reads as start..-1
+        let range = if RubyType::from_value(end) == RubyType::Float && f64::from_value_unchecked(end) == f64::INFINITY {
+            Range::new(start, -1i64, false)?.val
+        } else {
+            self.val
+        };
+        let (mut start, mut length) = (0, 0);
+        let found = protect(|| unsafe { rb_range_beg_len(range, &mut start, &mut length, len as i64, 0) })?;
+        // Qnil when out of range
+        Ok(if RTEST(found) { Some((start as usize, length as usize)) } else { None })
+    }
+}
+
+macro_rules! integer_range_conversions {
+    ($($ty:ident),*) => {
+        $(
+            impl From<ops::Range<$ty>> for Range {
+                fn from(range: ops::Range<$ty>) -> Self {
+                    Range::new(range.start, range.end, true).unwrap()
+                }
+            }
+
+            impl From<ops::RangeInclusive<$ty>> for Range {
+                fn from(range: ops::RangeInclusive<$ty>) -> Self {
+                    Range::new(*range.start(), *range.end(), false).unwrap()
+                }
+            }
+
+            impl From<ops::RangeFrom<$ty>> for Range {
+                fn from(range: ops::RangeFrom<$ty>) -> Self {
+                    Range::new(range.start, f64::INFINITY, false).unwrap()
+                }
+            }
+
+            // start...end, or start..end with end + 1
+            impl TryFrom<Range> for ops::Range<$ty> {
+                type Error = ConversionError;
+                fn try_from(range: Range) -> Result<Self, ConversionError> {
+                    let (start, end): ($ty, $ty) = (range.start()?, range.end()?);
+                    if range.is_exclusive() {
+                        return Ok(start..end);
+                    }
+                    match end.checked_add(1) {
+                        Some(end) => Ok(start..end),
+                        None => Err(ConversionError::out_of_range(range.values().1, stringify!($ty)))
+                    }
+                }
+            }
+
+            // start..end, or start...end with end - 1 (start...start is empty, so is start..=start - 1)
+            impl TryFrom<Range> for ops::RangeInclusive<$ty> {
+                type Error = ConversionError;
+                fn try_from(range: Range) -> Result<Self, ConversionError> {
+                    let (start, end): ($ty, $ty) = (range.start()?, range.end()?);
+                    if !range.is_exclusive() {
+                        return Ok(start..=end);
+                    }
+                    match end.checked_sub(1) {
+                        Some(end) => Ok(start..=end),
+                        None => Err(ConversionError::out_of_range(range.values().1, stringify!($ty)))
+                    }
+                }
+            }
+
+            // start..Float::INFINITY
+            impl TryFrom<Range> for ops::RangeFrom<$ty> {
+                type Error = ConversionError;
+                fn try_from(range: Range) -> Result<Self, ConversionError> {
+                    let start: $ty = range.start()?;
+                    match range.end::<f64>() {
+                        Ok(end) if end == f64::INFINITY => Ok(start..),
+                        _ => Err(ConversionError::new(RubyType::Float, range.values().1).expecting_class("Float::INFINITY"))
+                    }
+                }
+            }
+        )*
+    }
+}
+
+integer_range_conversions!(i32, i64, usize);
+
+impl FromValue for Range {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        if RTEST(unsafe { rb_obj_is_kind_of(value, rb_cRange) }) {
+            Ok(FromValue::from_value_unchecked(value))
+        } else {
+            Err(ConversionError::new(RubyType::Struct, value).expecting_class("Range"))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Range { val: value }
+    }
+}
+
+impl ToValue for Range {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Range({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
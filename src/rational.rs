@@ -0,0 +1,86 @@
+use ruby::*;
+use super::{protect, ToValue, FromValue, RubyType, ConversionError, RubyException, Numeric};
+use super::bytes::lossy_string;
+use std::convert::TryFrom;
+use std::fmt;
+
+// pub fn rb_rational_new(arg1: VALUE, arg2: VALUE) -> VALUE;
+// pub fn rb_rational_num(rat: VALUE) -> VALUE;
+// pub fn rb_rational_den(rat: VALUE) -> VALUE;
+
+// Ruby Rational, always reduced with a positive denominator:
+//
+//     let half = Rational::new(2, 4)?;
+//     assert_eq!(<(i64, i64)>::try_from(half)?, (1, 2));
+pub struct Rational {
+    val: VALUE
+}
+
+impl Rational {
+    // Raises ZeroDivisionError for a zero denominator
+    pub fn new(numerator: i64, denominator: i64) -> Result<Rational, RubyException> {
+        let (numerator, denominator) = (numerator.to_value(), denominator.to_value());
+        protect(|| unsafe { rb_rational_new(numerator, denominator) }).map(Rational::from_value_unchecked)
+    }
+
+    // Numerator as any integer type (or Bignum), failing with RangeError when it doesn't fit
+    pub fn numerator<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(unsafe { rb_rational_num(self.val) })
+    }
+
+    pub fn denominator<T>(&self) -> Result<T, ConversionError> where T: FromValue {
+        T::try_from_value(unsafe { rb_rational_den(self.val) })
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        Numeric::from_value_unchecked(self.val).0
+    }
+}
+
+impl TryFrom<(i64, i64)> for Rational {
+    type Error = RubyException;
+    fn try_from((numerator, denominator): (i64, i64)) -> Result<Self, RubyException> {
+        Rational::new(numerator, denominator)
+    }
+}
+
+// (numerator, denominator)
+impl TryFrom<Rational> for (i64, i64) {
+    type Error = ConversionError;
+    fn try_from(rational: Rational) -> Result<Self, ConversionError> {
+        Ok((rational.numerator()?, rational.denominator()?))
+    }
+}
+
+// (numerator, denominator), huge ones become infinite
+impl From<Rational> for (f64, f64) {
+    fn from(rational: Rational) -> Self {
+        let numerator = Numeric::from_value_unchecked(unsafe { rb_rational_num(rational.val) });
+        let denominator = Numeric::from_value_unchecked(unsafe { rb_rational_den(rational.val) });
+        (numerator.0, denominator.0)
+    }
+}
+
+impl FromValue for Rational {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value)  {
+            RubyType::Rational => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::Rational, value))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Rational { val: value }
+    }
+}
+
+impl ToValue for Rational {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rational({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
@@ -0,0 +1,78 @@
+use ruby::*;
+use super::{ToValue, RString};
+use super::bytes::string_bytes;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::{self, Utf8Error};
+
+// pub fn rb_str_locktmp(arg1: VALUE) -> VALUE;
+// pub fn rb_str_unlocktmp(arg1: VALUE) -> VALUE;
+
+// STR_TMPLOCK FL_USER7 (string.c)
+const STR_TMPLOCK: VALUE = 1 << (12 + 7);
+
+// Borrowed view of a String's bytes, without copying them. The string is locked while the view
+// exists, so ruby raises instead of modifying it underneath. The view borrows the `RString`, which
+// has to keep the string alive, e.g. as an argument of a method call:
+//
+//     ruby_method! {
+//         fn checksum(this: VALUE, payload: RString) -> i64 {
+//             RStr::new(&payload).as_bytes().iter().map(|&byte| byte as i64).sum()
+//         }
+//     }
+pub struct RStr<'a> {
+    val: VALUE,
+    // Whether this view took the lock, a string already locked by someone else is left to them
+    locked: bool,
+    _marker: PhantomData<&'a RString>
+}
+
+impl<'a> RStr<'a> {
+    pub fn new(string: &'a RString) -> Self {
+        let value = string.to_value();
+        // rb_str_locktmp raises on a string which is locked already
+        let flags = unsafe { (*(value as *const Struct_RBasic)).flags };
+        let locked = flags & STR_TMPLOCK == 0;
+        if locked {
+            unsafe { rb_str_locktmp(value) };
+        }
+        RStr { val: value, locked: locked, _marker: PhantomData }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { string_bytes(self.val) }
+    }
+
+    // The bytes as UTF-8, checked but not copied
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        str::from_utf8(self.as_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> Drop for RStr<'a> {
+    fn drop(&mut self) {
+        if self.locked {
+            unsafe { rb_str_unlocktmp(self.val) };
+        }
+    }
+}
+
+impl<'a> ToValue for RStr<'a> {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl<'a> fmt::Debug for RStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RStr({:?})", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
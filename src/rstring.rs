@@ -0,0 +1,202 @@
+use ruby::*;
+use macros::*;
+use super::{protect, ToValue, FromValue, RubyType, ConversionError, RubyException, Symbol, Bytes, Encoding, Range};
+use super::bytes::{string_bytes, lossy_string};
+use super::encoding::*;
+use std::fmt;
+use std::ptr;
+
+// pub fn rb_str_buf_new(arg1: ::libc::c_long) -> VALUE;
+// pub fn rb_str_cat(arg1: VALUE, arg2: *const ::libc::c_char, arg3: ::libc::c_long) -> VALUE;
+// pub fn rb_str_append(arg1: VALUE, arg2: VALUE) -> VALUE;
+// pub fn rb_str_strlen(arg1: VALUE) -> ::libc::c_long;
+// pub fn rb_str_capacity(arg1: VALUE) -> size_t;
+// pub fn rb_str_resize(arg1: VALUE, arg2: ::libc::c_long) -> VALUE;
+// pub fn rb_str_substr(arg1: VALUE, arg2: ::libc::c_long, arg3: ::libc::c_long) -> VALUE;
+// pub fn rb_str_freeze(arg1: VALUE) -> VALUE;
+// pub fn rb_str_dup(arg1: VALUE) -> VALUE;
+// pub fn rb_str_intern(arg1: VALUE) -> VALUE;
+// pub fn rb_str_scrub(arg1: VALUE, arg2: VALUE) -> VALUE;
+
+// Ruby String built and modified in place. Strings created from rust are UTF-8, and `write!`
+// appends to the ruby string directly:
+//
+//     let mut greeting = RString::with_capacity(64);
+//     write!(greeting, "Hello, {}!", name).unwrap();
+//
+// Modifying a frozen (or locked) string raises, which comes back as `Err`.
+pub struct RString {
+    val: VALUE
+}
+
+impl RString {
+    pub fn new() -> Self {
+        RString::from("")
+    }
+
+    // Empty UTF-8 string with room for `capacity` bytes
+    pub fn with_capacity(capacity: usize) -> Self {
+        unsafe {
+            let val = rb_str_buf_new(capacity as i64);
+            rb_enc_associate_index(val, rb_utf8_encindex());
+            RString { val: val }
+        }
+    }
+
+    pub fn push_str(&mut self, string: &str) -> Result<(), RubyException> {
+        let val = self.val;
+        protect(|| unsafe { rb_str_cat(val, string.as_ptr() as *const i8, string.len() as i64) })?;
+        Ok(())
+    }
+
+    // Appends another ruby string, raising Encoding::CompatibilityError when their encodings don't mix
+    pub fn append(&mut self, other: &RString) -> Result<(), RubyException> {
+        let val = self.val;
+        protect(|| unsafe { rb_str_append(val, other.val) })?;
+        Ok(())
+    }
+
+    // Length in bytes
+    pub fn len(&self) -> usize {
+        RSTRING_LEN(self.val) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Length in characters of the string's encoding
+    pub fn char_len(&self) -> usize {
+        unsafe { rb_str_strlen(self.val) as usize }
+    }
+
+    // Bytes the string can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        unsafe { rb_str_capacity(self.val) as usize }
+    }
+
+    // Truncates to `len` bytes or grows to it, filling with NUL bytes
+    pub fn resize(&mut self, len: usize) -> Result<(), RubyException> {
+        let val = self.val;
+        let old_len = self.len();
+        protect(|| unsafe { rb_str_resize(val, len as i64) })?;
+        if len > old_len {
+            unsafe { ptr::write_bytes(RSTRING_PTR(val).offset(old_len as isize) as *mut u8, 0, len - old_len) };
+        }
+        Ok(())
+    }
+
+    // `len` characters from character `start` on, None when `start` is past the end
+    pub fn substr(&self, start: usize, len: usize) -> Option<RString> {
+        let substring = unsafe { rb_str_substr(self.val, start as i64, len as i64) };
+        if NIL_P(substring) { None } else { Some(RString { val: substring }) }
+    }
+
+    // Characters in `range`, like String#[]. None when the range starts outside of the string.
+    pub fn slice<R>(&self, range: R) -> Result<Option<RString>, RubyException> where R: Into<Range> {
+        match range.into().start_len(self.char_len())? {
+            Some((start, len)) => Ok(self.substr(start, len)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn freeze(&self) {
+        unsafe { rb_str_freeze(self.val) };
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        RTEST(unsafe { rb_obj_frozen_p(self.val) })
+    }
+
+    // Unfrozen copy
+    pub fn dup(&self) -> RString {
+        RString { val: unsafe { rb_str_dup(self.val) } }
+    }
+
+    // Symbol of the same name, fails on a string with broken encoding
+    pub fn intern(&self) -> Result<Symbol, RubyException> {
+        let val = self.val;
+        protect(|| unsafe { rb_str_intern(val) }).map(Symbol::from_value_unchecked)
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        Encoding::of(self.val).unwrap_or_else(Encoding::ascii_8bit)
+    }
+
+    // Copy transcoded to `to`, raising Encoding::UndefinedConversionError (or InvalidByteSequenceError)
+    // for characters which can't be converted
+    pub fn encode(&self, to: Encoding) -> Result<RString, RubyException> {
+        let val = self.val;
+        protect(|| unsafe { rb_str_encode(val, to.to_value(), 0, RUBY_Qnil as VALUE) }).map(RString::from_value_unchecked)
+    }
+
+    // Reinterprets the same bytes as being in `encoding`
+    pub fn force_encoding(&mut self, encoding: Encoding) -> Result<(), RubyException> {
+        let val = self.val;
+        protect(|| unsafe {
+            rb_str_modify(val);
+            rb_enc_associate(val, encoding.as_ptr())
+        })?;
+        Ok(())
+    }
+
+    // String#valid_encoding?
+    pub fn is_valid_encoding(&self) -> bool {
+        unsafe { rb_enc_str_coderange(self.val) != ENC_CODERANGE_BROKEN }
+    }
+
+    pub fn is_ascii_only(&self) -> bool {
+        unsafe { rb_enc_str_asciionly_p(self.val) != 0 }
+    }
+
+    // Copy with invalid byte sequences replaced by `replacement`, or the encoding's default
+    // replacement character (U+FFFD or "?")
+    pub fn scrub(&self, replacement: Option<&str>) -> Result<RString, RubyException> {
+        let val = self.val;
+        let replacement = replacement.map_or(RUBY_Qnil as VALUE, |replacement| RString::from(replacement).to_value());
+        let scrubbed = protect(|| unsafe { rb_str_scrub(val, replacement) })?;
+        // nil when there was nothing to replace
+        Ok(if NIL_P(scrubbed) { self.dup() } else { RString { val: scrubbed } })
+    }
+
+    // Copy of the contents, see `RStr` to borrow them instead
+    pub fn to_bytes(&self) -> Bytes {
+        Bytes::from(unsafe { string_bytes(self.val) })
+    }
+}
+
+impl<'a> From<&'a str> for RString {
+    fn from(string: &'a str) -> Self {
+        RString { val: unsafe { rb_utf8_str_new(string.as_ptr() as *const i8, string.len() as i64) } }
+    }
+}
+
+impl fmt::Write for RString {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.push_str(string).map_err(|_| fmt::Error)
+    }
+}
+
+impl FromValue for RString {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        match RubyType::from_value(value)  {
+            RubyType::String => Ok(FromValue::from_value_unchecked(value)),
+            _ => Err(ConversionError::new(RubyType::String, value))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        RString { val: value }
+    }
+}
+
+impl ToValue for RString {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for RString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RString({})", lossy_string(unsafe { rb_inspect(self.val) }) )
+    }
+}
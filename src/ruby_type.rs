@@ -1,7 +1,8 @@
 use super::ruby::{self, VALUE};
 use super::rb_type;
+use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RubyType {
     Nil, // T_NIL
     Object, // T_OBJECT
@@ -49,4 +50,35 @@ impl RubyType {
             _ => RubyType::Object,
         }
     }
+
+    // Name of the type as Ruby prints it in TypeError messages
+    pub fn name(&self) -> &'static str {
+        match *self {
+            RubyType::Nil => "nil",
+            RubyType::Object => "Object",
+            RubyType::Class => "Class",
+            RubyType::Module => "Module",
+            RubyType::Float => "Float",
+            RubyType::String => "String",
+            RubyType::Regexp => "Regexp",
+            RubyType::Array => "Array",
+            RubyType::Hash => "Hash",
+            RubyType::Struct => "Struct",
+            RubyType::Bignum => "Bignum",
+            RubyType::Fixnum => "Fixnum",
+            RubyType::Complex => "Complex",
+            RubyType::Rational => "Rational",
+            RubyType::File => "File",
+            RubyType::True => "true",
+            RubyType::False => "false",
+            RubyType::Data => "Data",
+            RubyType::Symbol => "Symbol",
+        }
+    }
+}
+
+impl fmt::Display for RubyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
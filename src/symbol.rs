@@ -0,0 +1,85 @@
+use ruby::*;
+use macros::*;
+use super::{FromValue, ToValue, ConversionError, RubyType, Id};
+use super::bytes::lossy_string;
+use std::fmt;
+
+// pub fn rb_sym2id(arg1: VALUE) -> ID;
+// pub fn rb_id2sym(arg1: ID) -> VALUE;
+// pub fn rb_sym2str(arg1: VALUE) -> VALUE;
+
+// Ruby Symbol, either static (an immediate, interned for good) or dynamic (a GC managed object,
+// e.g. from String#to_sym)
+pub struct Symbol {
+    val: VALUE
+}
+
+impl Symbol {
+    pub fn new(name: &str) -> Self {
+        Symbol::from_id(Id::new(name))
+    }
+
+    pub fn from_id(id: Id) -> Self {
+        Symbol { val: unsafe { rb_id2sym(id.as_raw()) } }
+    }
+
+    // Interns the symbol if it's dynamic, pinning it
+    pub fn id(&self) -> Id {
+        Id::from_raw(unsafe { rb_sym2id(self.val) })
+    }
+
+    // Fails for a symbol whose name isn't valid in (or convertible to) UTF-8, e.g. `"\xff".b.to_sym`
+    pub fn name(&self) -> Result<String, ConversionError> {
+        String::try_from_value(unsafe { rb_sym2str(self.val) })
+    }
+
+    pub fn is_static(&self) -> bool {
+        RB_STATIC_SYM_P(self.val)
+    }
+}
+
+impl<'a> From<&'a str> for Symbol {
+    fn from(name: &'a str) -> Self {
+        Symbol::new(name)
+    }
+}
+
+impl FromValue for Symbol {
+    fn try_from_value(value: VALUE) -> Result<Self, ConversionError> {
+        if RB_SYMBOL_P(value) {
+            Ok(FromValue::from_value_unchecked(value))
+        } else {
+            Err(ConversionError::new(RubyType::Symbol, value))
+        }
+    }
+    fn from_value_unchecked(value: VALUE) -> Self {
+        Symbol { val: value }
+    }
+}
+
+impl ToValue for Symbol {
+    fn to_value(&self) -> VALUE {
+        self.val
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Symbol({})", lossy_string(unsafe { rb_sym2str(self.val) }))
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", lossy_string(unsafe { rb_sym2str(self.val) }))
+    }
+}
+
+// Symbol for a literal name, the ID is interned once and cached: `hash.aref(sym!("name").to_value())`
+#[macro_export]
+macro_rules! sym {
+    ($name:expr) => {{
+        static ID: $crate::StaticId = $crate::StaticId::new($name);
+        $crate::Symbol::from_id(ID.id())
+    }}
+}
@@ -1,12 +1,12 @@
 use super::ruby::{self, VALUE};
-use super::Nil;
+use super::{Nil, U64};
 use super::macros::*;
-use std::ffi::CString;
 
 pub trait ToValue {
     fn to_value(&self) -> VALUE;
 }
 
+// The VALUE as is, no conversion. Since VALUE is u64 this is also what `u64` converts with, see `U64`.
 impl ToValue for VALUE {
     fn to_value(&self) -> VALUE {
         *self
@@ -22,27 +22,43 @@ impl ToValue for bool {
     }
 }
 
-impl ToValue for i64 {
-    fn to_value(&self) -> VALUE {
-        INT2FIX(*self)
+// Fixnum when the number fits in one, Bignum otherwise
+macro_rules! integer_to_value {
+    ($convert:ident as $wide:ty, $($ty:ty),*) => {
+        $(
+            impl ToValue for $ty {
+                fn to_value(&self) -> VALUE {
+                    unsafe { ruby::$convert(*self as $wide) }
+                }
+            }
+        )*
     }
 }
 
-impl ToValue for i32 {
+integer_to_value!(rb_int2inum as i64, i8, i16, i32, i64, isize);
+integer_to_value!(rb_ull2inum as u64, u8, u16, u32, usize);
+
+impl ToValue for U64 {
     fn to_value(&self) -> VALUE {
-        INT2FIX(*self as i64)
+        unsafe { ruby::rb_ull2inum(self.0) }
     }
 }
 
 impl ToValue for f64 {
     fn to_value(&self) -> VALUE {
-        unsafe { ruby::rb_float_new(*self) }
+        rb_float_flonum(*self).unwrap_or_else(|| unsafe { ruby::rb_float_new_in_heap(*self) })
     }
 }
 
 impl ToValue for String {
     fn to_value(&self) -> VALUE {
-        unsafe { ruby::rb_str_new_cstr(CString::new(self.clone()).unwrap().as_ptr() as *const i8) }
+        unsafe { ruby::rb_utf8_str_new(self.as_ptr() as *const i8, self.len() as i64) }
+    }
+}
+
+impl ToValue for () {
+    fn to_value(&self) -> VALUE {
+        ruby::RUBY_Qnil as VALUE
     }
 }
 
@@ -0,0 +1,151 @@
+use ruby::*;
+use super::{RubyType, ConversionError, Mark};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+// pub fn rb_data_typed_object_alloc(klass: VALUE, datap: *mut ::libc::c_void,
+//                                   arg1: *const rb_data_type_t) -> VALUE;
+// pub fn rb_typeddata_is_kind_of(arg1: VALUE, arg2: *const rb_data_type_t) -> ::libc::c_int;
+// pub fn rb_check_typeddata(arg1: VALUE, arg2: *const rb_data_type_t) -> *mut ::libc::c_void;
+
+// RUBY_TYPED_FREE_IMMEDIATELY from ruby.h, dfree is called during GC sweep rather than deferred
+const RUBY_TYPED_FREE_IMMEDIATELY: VALUE = 1;
+
+// rb_data_type_t describing how ruby should treat a boxed rust value of type T.
+// One static instance per type is declared by the `typed_data!` macro.
+pub struct RubyDataType {
+    name: &'static str,
+    data_type: rb_data_type_t
+}
+
+// Only ever read by ruby and never mutated after construction
+unsafe impl Sync for RubyDataType {}
+
+impl RubyDataType {
+    // `name` has to be NUL terminated, `typed_data!` takes care of that
+    pub const fn new<T>(name: &'static str) -> Self {
+        RubyDataType {
+            name: name,
+            data_type: Struct_rb_data_type_struct {
+                wrap_struct_name: name.as_ptr() as *const i8,
+                function: Struct_Unnamed18 {
+                    dmark: None,
+                    dfree: Some(typed_data_free::<T>),
+                    dsize: Some(typed_data_size::<T>),
+                    reserved: [ptr::null_mut(); 2]
+                },
+                parent: ptr::null(),
+                data: ptr::null_mut(),
+                flags: RUBY_TYPED_FREE_IMMEDIATELY
+            }
+        }
+    }
+
+    // Same as `new`, but also reports the ruby objects held by T to the GC through its `Mark` impl
+    pub const fn with_mark<T>(name: &'static str) -> Self where T: Mark {
+        let mut data_type = RubyDataType::new::<T>(name);
+        data_type.data_type.function.dmark = Some(typed_data_mark::<T>);
+        data_type
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name.trim_end_matches('\0')
+    }
+
+    fn as_ptr(&self) -> *const rb_data_type_t {
+        &self.data_type
+    }
+}
+
+unsafe extern "C" fn typed_data_mark<T>(data: *mut ::libc::c_void) where T: Mark {
+    (*(data as *const T)).mark();
+}
+
+unsafe extern "C" fn typed_data_free<T>(data: *mut ::libc::c_void) {
+    drop(Box::from_raw(data as *mut T));
+}
+
+unsafe extern "C" fn typed_data_size<T>(_data: *const ::libc::c_void) -> size_t {
+    mem::size_of::<T>() as size_t
+}
+
+// Implemented by `typed_data!` for every rust type which can be wrapped into a ruby object
+pub trait DataType: Sized + 'static {
+    fn data_type() -> &'static RubyDataType;
+}
+
+// Declares the static rb_data_type_t for a rust type.
+// Types holding ruby objects pass `mark` to have their `Mark` impl called by the GC.
+//
+//     struct Point { x: f64, y: f64 }
+//     typed_data!(Point, "Point");
+//     typed_data!(Graph, "Graph", mark);
+#[macro_export]
+macro_rules! typed_data {
+    ($ty:ty, $name:expr) => {
+        impl $crate::DataType for $ty {
+            fn data_type() -> &'static $crate::RubyDataType {
+                static DATA_TYPE: $crate::RubyDataType = $crate::RubyDataType::new::<$ty>(concat!($name, "\0"));
+                &DATA_TYPE
+            }
+        }
+    };
+    ($ty:ty, $name:expr, mark) => {
+        impl $crate::DataType for $ty {
+            fn data_type() -> &'static $crate::RubyDataType {
+                static DATA_TYPE: $crate::RubyDataType = $crate::RubyDataType::with_mark::<$ty>(concat!($name, "\0"));
+                &DATA_TYPE
+            }
+        }
+    };
+}
+
+// Moves rust values into ruby objects and borrows them back.
+// The value lives as long as the ruby object and is dropped when the object is garbage collected.
+pub struct TypedData<T> {
+    _marker: PhantomData<T>
+}
+
+impl<T> TypedData<T> where T: DataType {
+    // Boxes `data` into a new instance of `class`, or a hidden object (invisible to ruby code) for class 0
+    pub fn wrap(class: VALUE, data: T) -> VALUE {
+        let ptr = Box::into_raw(Box::new(data));
+        unsafe { rb_data_typed_object_alloc(class, ptr as *mut ::libc::c_void, T::data_type().as_ptr()) }
+    }
+
+    pub fn is_a(value: VALUE) -> bool {
+        unsafe { rb_typeddata_is_kind_of(value, T::data_type().as_ptr()) != 0 }
+    }
+
+    // Borrows the wrapped value. The caller picks the lifetime, so it has to make sure that:
+    // - the ruby object stays alive (and reachable by the GC) for `'a`, which holds for the duration
+    //   of a method call on it
+    // - no `get_mut` borrow of the same object is live at the same time
+    pub unsafe fn get<'a>(value: VALUE) -> Result<&'a T, ConversionError> {
+        Self::check(value).map(|data| &*data)
+    }
+
+    // Same as `get`, and no other borrow of the same object (from `get` or `get_mut`) may be live
+    // for `'a`, e.g. through ruby code calling back into a method on the same object
+    pub unsafe fn get_mut<'a>(value: VALUE) -> Result<&'a mut T, ConversionError> {
+        Self::check(value).map(|data| &mut *data)
+    }
+
+    // Shared borrow without the type check, see `get`
+    pub unsafe fn get_ref_unchecked<'a>(value: VALUE) -> &'a T {
+        &*((*(value as *const Struct_RTypedData)).data as *const T)
+    }
+
+    pub unsafe fn get_unchecked<'a>(value: VALUE) -> &'a mut T {
+        &mut *((*(value as *const Struct_RTypedData)).data as *mut T)
+    }
+
+    fn check(value: VALUE) -> Result<*mut T, ConversionError> {
+        if Self::is_a(value) {
+            Ok(unsafe { Self::get_unchecked(value) as *mut T })
+        } else {
+            Err(ConversionError::new(RubyType::Data, value).expecting_class(T::data_type().name()))
+        }
+    }
+}
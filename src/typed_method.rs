@@ -0,0 +1,130 @@
+use ruby::VALUE;
+use super::{FromValue, ToValue, ConversionError, RubyException};
+
+// Converts the argument at `position` (receiver excluded) and moves on to the next one.
+// Used by `ruby_method!`, so the TypeError can tell which argument was wrong.
+pub fn convert_argument<T>(value: VALUE, position: &mut usize) -> Result<T, ConversionError> where T: FromValue {
+    let result = T::try_from_value(value).map_err(|error| error.at_position(*position));
+    *position += 1;
+    result
+}
+
+// What a method defined with `ruby_method!` can return: any `ToValue`, or a `Result` whose error
+// is raised as an exception (e.g. the ArgumentError of `KwArgs::parse`).
+pub trait MethodReturn {
+    fn into_method_result(self) -> Result<VALUE, RubyException>;
+}
+
+impl<T> MethodReturn for T where T: ToValue {
+    fn into_method_result(self) -> Result<VALUE, RubyException> {
+        Ok(self.to_value())
+    }
+}
+
+impl<T, E> MethodReturn for Result<T, E> where T: ToValue, E: Into<RubyException> {
+    fn into_method_result(self) -> Result<VALUE, RubyException> {
+        self.map(|value| value.to_value()).map_err(Into::into)
+    }
+}
+
+// Defines a ruby method from a plain rust function.
+// Every argument is converted with `FromValue` (a failed conversion raises TypeError naming the
+// argument), the result with `ToValue`, and the body is guarded against panics.
+// The receiver is either converted with `FromValue` or, when taken by reference, borrowed from a `TypedData` object.
+// The borrow lasts for the call only. An `&mut` receiver must not be borrowed again meanwhile, so such
+// a method must not (through ruby code it calls) re-enter a method on the same object.
+//
+//     ruby_method! {
+//         fn add(this: &Point, a: i64, b: f64) -> String {
+//             format!("{}", this.x + a as f64 + b)
+//         }
+//     }
+//
+// `add` is the extern "C" function pointer with one VALUE per argument, so it can be registered directly:
+//
+//     Class::define("Point").method("add", add).build();
+//     add.define_method(point_class, "add");
+//
+// A trailing `**name` collects the keyword arguments into an `Option<Hash>` (None when none were
+// passed). The method then takes argc/argv (-1 arity), ruby checks the number of positional
+// arguments and raises ArgumentError when it is wrong:
+//
+//     ruby_method! {
+//         fn greet(this: VALUE, name: String, **options) -> Result<String, RubyException> {
+//             let options = KwArgs::parse(options, &[], &["greeting"])?;
+//             let greeting = options.get("greeting")?.unwrap_or("Hello".to_string());
+//             Ok(format!("{}, {}", greeting, name))
+//         }
+//     }
+#[macro_export]
+macro_rules! ruby_method {
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: &mut $recv:ty $(, $arg:ident: $ty:ty)*, **$kw:ident) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define_kw $(#[$attr])* [$vis] $name, $this, &mut $recv, unsafe { $crate::TypedData::<$recv>::get_mut($this) }, ($($arg: $ty),*), $kw, $ret, $body);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: &$recv:ty $(, $arg:ident: $ty:ty)*, **$kw:ident) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define_kw $(#[$attr])* [$vis] $name, $this, &$recv, unsafe { $crate::TypedData::<$recv>::get($this) }, ($($arg: $ty),*), $kw, $ret, $body);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: $recv:ty $(, $arg:ident: $ty:ty)*, **$kw:ident) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define_kw $(#[$attr])* [$vis] $name, $this, $recv, <$recv as $crate::FromValue>::try_from_value($this), ($($arg: $ty),*), $kw, $ret, $body);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: &mut $recv:ty $(, $arg:ident: $ty:ty)*) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define $(#[$attr])* [$vis] $name, $this, &mut $recv, unsafe { $crate::TypedData::<$recv>::get_mut($this) }, ($($arg: $ty),*), $ret, $body);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: &$recv:ty $(, $arg:ident: $ty:ty)*) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define $(#[$attr])* [$vis] $name, $this, &$recv, unsafe { $crate::TypedData::<$recv>::get($this) }, ($($arg: $ty),*), $ret, $body);
+    };
+    ($(#[$attr:meta])* $vis:vis fn $name:ident($this:ident: $recv:ty $(, $arg:ident: $ty:ty)*) -> $ret:ty $body:block) => {
+        $crate::ruby_method!(@define $(#[$attr])* [$vis] $name, $this, $recv, <$recv as $crate::FromValue>::try_from_value($this), ($($arg: $ty),*), $ret, $body);
+    };
+    (@define $(#[$attr:meta])* [$vis:vis] $name:ident, $this:ident, $recv_ty:ty, $convert:expr, ($($arg:ident: $ty:ty),*), $ret:ty, $body:block) => {
+        $(#[$attr])*
+        #[allow(non_upper_case_globals)]
+        $vis const $name: extern "C" fn($this: $crate::VALUE $(, $arg: $crate::VALUE)*) -> $crate::VALUE = {
+            fn body($this: $recv_ty $(, $arg: $ty)*) -> $ret $body
+
+            extern "C" fn shim($this: $crate::VALUE $(, $arg: $crate::VALUE)*) -> $crate::VALUE {
+                $crate::catch_errors(move || {
+                    #[allow(unused_mut, unused_variables)]
+                    let mut position = 0;
+                    let $this = $convert?;
+                    $(let $arg: $ty = $crate::convert_argument($arg, &mut position)?;)*
+                    let result: $ret = body($this $(, $arg)*);
+                    $crate::MethodReturn::into_method_result(result)
+                })
+            }
+
+            shim
+        };
+    };
+    (@define_kw $(#[$attr:meta])* [$vis:vis] $name:ident, $this:ident, $recv_ty:ty, $convert:expr, ($($arg:ident: $ty:ty),*), $kw:ident, $ret:ty, $body:block) => {
+        $(#[$attr])*
+        #[allow(non_upper_case_globals)]
+        $vis const $name: extern "C" fn(::std::os::raw::c_int, *const $crate::VALUE, $crate::VALUE) -> $crate::VALUE = {
+            fn body($this: $recv_ty $(, $arg: $ty)*, $kw: Option<$crate::Hash>) -> $ret $body
+
+            extern "C" fn shim(argc: ::std::os::raw::c_int, argv: *const $crate::VALUE, $this: $crate::VALUE) -> $crate::VALUE {
+                $crate::catch_errors(move || {
+                    // Every positional argument is required, followed by the keywords
+                    const SPEC: $crate::ArgSpec = $crate::ArgSpec {
+                        lead: <[&str]>::len(&[$(stringify!($arg)),*]),
+                        optional: 0,
+                        splat: false,
+                        trailing: 0,
+                        keywords: true,
+                        block: false
+                    };
+                    // argc/argv as ruby passed them
+                    let args = unsafe { $crate::Args::scan_spec(argc, argv, SPEC) }?;
+                    #[allow(unused_mut, unused_variables)]
+                    let mut positions = 0..;
+                    let $this = $convert?;
+                    $(let $arg: $ty = args.required(positions.next().unwrap())?;)*
+                    let result: $ret = body($this $(, $arg)*, args.keywords());
+                    $crate::MethodReturn::into_method_result(result)
+                })
+            }
+
+            shim
+        };
+    };
+}